@@ -0,0 +1,144 @@
+use crate::draw::{Band, rgb};
+use crate::math::Vec3;
+use crate::projector::Projector;
+use crate::star::Light;
+
+/// Capa de aire alrededor de un cuerpo. La densidad cae exponencialmente con
+/// la altura; Rayleigh da el color del cielo (y se come ese color en los
+/// caminos largos, de ahí el rojizo del terminador) y Mie el brillo blanquecino
+/// hacia el Sol.
+#[derive(Clone, Copy, Debug)]
+pub struct Atmosphere {
+    /// Espesor de la capa sobre el radio del cuerpo (unidades de mundo)
+    pub height: f32,
+    /// Densidad en la superficie; ~1 es una atmósfera "terrestre"
+    pub density: f32,
+    /// Coeficientes de Rayleigh por canal (0..1)
+    pub rayleigh: Vec3,
+    /// Coeficientes de Mie por canal (0..1)
+    pub mie: Vec3,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self {
+            height: 2.0,
+            density: 1.0,
+            rayleigh: Vec3::new(0.25, 0.5, 1.0),
+            mie: Vec3::new(0.4, 0.4, 0.4),
+        }
+    }
+}
+
+// pasos de integración a lo largo de cada rayo
+const STEPS: usize = 10;
+// asimetría de Mie (Henyey–Greenstein)
+const MIE_G: f32 = 0.76;
+// intensidad del Sol que entra a la capa
+const SUN_POWER: f32 = 1.5;
+
+fn exp3(v: Vec3) -> Vec3 {
+    Vec3::new((-v.x).exp(), (-v.y).exp(), (-v.z).exp())
+}
+
+fn mul3(a: Vec3, b: Vec3) -> Vec3 {
+    Vec3::new(a.x * b.x, a.y * b.y, a.z * b.z)
+}
+
+/// Distancia desde `p` (dentro de la esfera) hasta salir por la dirección `dir`
+fn exit_distance(p: Vec3, dir: Vec3, radius: f32) -> f32 {
+    let b = p.dot(dir);
+    let c = p.dot(p) - radius * radius;
+    let disc = (b * b - c).max(0.0);
+    (-b + disc.sqrt()).max(0.0)
+}
+
+/// Dibuja el halo de la atmósfera de una esfera de cámara (`center`, `radius`)
+/// mezclándolo sobre lo que ya hay en `fb`. Va después de los cuerpos opacos:
+/// el z-buffer corta cada rayo donde hay superficie, así que el aire tiñe al
+/// planeta (y a sus lunas) por delante y brilla sobre el espacio en el borde.
+/// No escribe profundidad. `lights` son las estrellas (direcciones en cámara):
+/// cada una dispersa su propio color.
+pub fn draw_atmosphere(
+    fb: &mut Band,
+    proj: &Projector,
+    center: Vec3,
+    radius: f32,
+    atm: &Atmosphere,
+    lights: &[Light],
+) {
+    if atm.height <= 0.0 || atm.density <= 0.0 { return; }
+    let outer = radius + atm.height;
+    let Some((x0, y0, x1, y1)) = proj.sphere_bounds(center, outer) else { return; };
+    let Some((y0, y1)) = fb.clip_rows(y0, y1) else { return; };
+
+    let scale_h = atm.height * 0.25;
+    // la integral sobre todo el espesor queda del orden de `density`
+    let k = atm.density / atm.height;
+    let extinction = atm.rayleigh.add(atm.mie);
+    let cc = center.dot(center) - outer * outer;
+    // dispersión de cada estrella en el píxel actual (se reusa entre píxeles)
+    let mut scatter: Vec<Vec3> = Vec::with_capacity(lights.len());
+
+    for yy in y0..=y1 {
+        for xx in x0..=x1 {
+            let d = proj.ray_dir(xx as f32 + 0.5, yy as f32 + 0.5);
+            let a = d.dot(d);
+            let b = d.dot(center);
+            let disc = b * b - a * cc;
+            if disc < 0.0 { continue; }
+            let sq = disc.sqrt();
+            let t_in = ((b - sq) / a).max(proj.z_near);
+            let i = fb.index(xx, yy);
+            // el rayo termina en la cáscara o en lo primero opaco que haya
+            let t_out = ((b + sq) / a).min(fb.depth[i]);
+            if t_out <= t_in { continue; }
+
+            let view = d.norm();
+            // lo que dispersa cada estrella según el ángulo con la vista
+            scatter.clear();
+            scatter.extend(lights.iter().map(|l| {
+                let mu = view.dot(l.dir);
+                let phase_r = 0.75 * (1.0 + mu * mu);
+                let g2 = MIE_G * MIE_G;
+                let phase_m = 1.5 * (1.0 - g2) / (2.0 + g2) * (1.0 + mu * mu)
+                    / (1.0 + g2 - 2.0 * MIE_G * mu).powf(1.5);
+                atm.rayleigh.mul(phase_r).add(atm.mie.mul(phase_m))
+            }));
+
+            let ds = (t_out - t_in) / STEPS as f32;
+            let mut od_view = 0.0;
+            let mut inscatter = Vec3::new(0.0, 0.0, 0.0);
+
+            for s in 0..STEPS {
+                let t = t_in + (s as f32 + 0.5) * ds;
+                let p = d.mul(t).sub(center);
+                let r = p.len();
+                let h = (r - radius).max(0.0);
+                let rho = (-h / scale_h).exp() * k;
+                od_view += rho * ds;
+
+                let up = p.mul(1.0 / r.max(1e-6));
+                let horizon = -(1.0 - (radius / r.max(radius)).powi(2)).max(0.0).sqrt();
+                for (l, sc) in lights.iter().zip(&scatter) {
+                    // sombra del propio cuerpo: la estrella queda tapada detrás del limbo
+                    let mu_s = up.dot(l.dir);
+                    let lit = ((mu_s - horizon + 0.05) / 0.1).clamp(0.0, 1.0);
+                    if lit <= 0.0 { continue; }
+
+                    // camino de la luz hasta este punto (rasante = largo = rojo)
+                    let od_sun = rho * exit_distance(p, l.dir, outer);
+                    let trans = exp3(extinction.mul(od_view + od_sun));
+                    inscatter = inscatter.add(mul3(mul3(trans, *sc), l.color).mul(rho * ds * lit));
+                }
+            }
+
+            let t_view = exp3(extinction.mul(od_view));
+            let dst = fb.color[i];
+            let ch = |sh: u32| ((dst >> sh) & 0xFF) as f32 / 255.0;
+            let out = mul3(Vec3::new(ch(16), ch(8), ch(0)), t_view).add(inscatter.mul(SUN_POWER));
+            let to8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0) as u8;
+            fb.color[i] = rgb(to8(out.x), to8(out.y), to8(out.z));
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::image_io::{Image, write_png};
+use crate::material::PlanetMaterial;
+use crate::math::Vec3;
+use crate::noise::Noise;
+use crate::shading::static_surface;
+use crate::texture::{SurfaceMaps, Texture, equirect_dir};
+
+// Horneado: la parte estática de un material procedural (continentes, roca,
+// hielo, ciudades) se evalúa una vez a mapas equirectangulares y en cada frame
+// se muestrea en vez de recalcular el ruido; lo animado (lava, nubes, auroras)
+// sigue saliendo en tiempo real encima.
+
+/// Ancho por defecto de los mapas horneados (el alto es la mitad)
+pub const DEFAULT_BAKE_WIDTH: usize = 2048;
+
+/// Hornea la superficie estática de `material` (sembrada con `seed`) a mapas
+/// de `width`×`width/2`: albedo, especular si algo brilla y nocturno si algo
+/// emite. `None` si el modelo es animado (estrella, gigante gaseoso) o ya
+/// tiene mapas. Las filas se reparten entre `threads` hilos.
+pub fn bake_surface(material: &PlanetMaterial, seed: u32, width: usize, threads: usize) -> Option<SurfaceMaps> {
+    if !material.kind.is_static() || material.maps.is_some() { return None; }
+    let (w, h) = (width.max(2), (width / 2).max(1));
+    let noise = Noise::new(seed);
+    let pack = |c: Vec3| {
+        let ch = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u32;
+        (ch(c.x) << 16) | (ch(c.y) << 8) | ch(c.z)
+    };
+
+    // por texel: albedo, brillo (gris) y emisión
+    let mut texels = vec![(0u32, 0u32, 0u32); w * h];
+    let rows_per = h.div_ceil(threads.max(1));
+    thread::scope(|s| {
+        for (band, chunk) in texels.chunks_mut(rows_per * w).enumerate() {
+            let (noise, pack) = (&noise, &pack);
+            s.spawn(move || {
+                for (k, t) in chunk.iter_mut().enumerate() {
+                    let (x, y) = (k % w, band * rows_per + k / w);
+                    let local = equirect_dir((x as f32 + 0.5) / w as f32, (y as f32 + 0.5) / h as f32);
+                    let Some(surf) = static_surface(noise, material, local) else { continue; };
+                    let g = pack(Vec3::new(surf.gloss, surf.gloss, surf.gloss));
+                    *t = (pack(surf.albedo), g, pack(surf.emission));
+                }
+            });
+        }
+    });
+
+    let map = |f: fn(&(u32, u32, u32)) -> u32| {
+        Texture::from_image(Image { w, h, pixels: texels.iter().map(f).collect() })
+    };
+    let glossy = texels.iter().any(|t| t.1 != 0) && material.specular > 0.0;
+    let glowing = texels.iter().any(|t| t.2 != 0);
+    Some(SurfaceMaps {
+        albedo: map(|t| t.0),
+        normal: None,
+        specular: glossy.then(|| map(|t| t.1)),
+        night: glowing.then(|| map(|t| t.2)),
+    })
+}
+
+/// Escribe los mapas como PNG en `dir` (`<nombre>_albedo.png`, ...), listos
+/// para abrir en otro programa o para usar con kind = "textured"; devuelve
+/// las rutas escritas
+pub fn export_maps(maps: &SurfaceMaps, dir: &Path, name: &str) -> io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    let mut written = Vec::new();
+    for (suffix, tex) in [
+        ("albedo", Some(&maps.albedo)),
+        ("normal", maps.normal.as_ref()),
+        ("specular", maps.specular.as_ref()),
+        ("night", maps.night.as_ref()),
+    ] {
+        let Some(tex) = tex else { continue; };
+        let img = tex.to_image();
+        let path = dir.join(format!("{}_{}.png", stem, suffix));
+        write_png(&path, img.w, img.h, &img.pixels)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::shading::PlanetKind;
+
+    /// Diferencia máxima por canal, en pasos de 8 bits
+    fn off_by(texel: u32, c: Vec3) -> f32 {
+        let ch = |sh: u32| ((texel >> sh) & 0xFF) as f32;
+        let want = |v: f32| v.clamp(0.0, 1.0) * 255.0;
+        (ch(16) - want(c.x)).abs().max((ch(8) - want(c.y)).abs()).max((ch(0) - want(c.z)).abs())
+    }
+
+    #[test]
+    fn bake_matches_static_surface_at_texel_centres() {
+        let (w, h, seed) = (32, 16, 7);
+        let noise = Noise::new(seed);
+        for kind in [PlanetKind::Rocky, PlanetKind::Ice, PlanetKind::Volcanic] {
+            let m = PlanetMaterial::preset(kind);
+            let maps = bake_surface(&m, seed, w, 3).expect("modelo estático");
+            let albedo = maps.albedo.to_image();
+            let night = maps.night.as_ref().map(|t| t.to_image());
+            assert_eq!((albedo.w, albedo.h), (w, h));
+            for y in 0..h {
+                for x in 0..w {
+                    let local = equirect_dir((x as f32 + 0.5) / w as f32, (y as f32 + 0.5) / h as f32);
+                    let surf = static_surface(&noise, &m, local).unwrap();
+                    let i = y * w + x;
+                    assert!(off_by(albedo.pixels[i], surf.albedo) <= 1.0, "{:?} albedo en {:?}", kind, (x, y));
+                    let glow = night.as_ref().map_or(0, |n| n.pixels[i]);
+                    assert!(off_by(glow, surf.emission) <= 1.0, "{:?} emisión en {:?}", kind, (x, y));
+                }
+            }
+            // mismo resultado con cualquier reparto de filas
+            let single = bake_surface(&m, seed, w, 1).unwrap();
+            assert!(single.albedo.to_image().pixels == albedo.pixels);
+        }
+    }
+
+    #[test]
+    fn animated_or_mapped_surfaces_are_not_baked() {
+        for kind in [PlanetKind::Star, PlanetKind::GasGiant] {
+            assert!(bake_surface(&PlanetMaterial::preset(kind), 1, 16, 2).is_none(), "{:?}", kind);
+        }
+        let mut m = PlanetMaterial::preset(PlanetKind::Rocky);
+        m.maps = Some(Arc::new(bake_surface(&m, 1, 16, 2).unwrap()));
+        assert!(bake_surface(&m, 1, 16, 2).is_none());
+    }
+}
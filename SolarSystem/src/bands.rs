@@ -0,0 +1,69 @@
+use std::sync::Mutex;
+use std::thread;
+
+use crate::draw::{Band, FrameBuffer, Prim};
+
+/// Franjas por hilo: con más franjas que hilos, los que terminan rápido (cielo
+/// vacío) siguen con otras y un planeta grande no deja a uno solo trabajando
+const BANDS_PER_THREAD: usize = 4;
+
+/// Algo que dibujar en las filas de pantalla `rows` (inclusive). `draw` se
+/// llama una vez por franja que lo toca y tiene que recortarse a ella.
+pub struct Job<'a> {
+    pub rows: (i32, i32),
+    pub draw: Box<dyn Fn(&mut Band) + Sync + 'a>,
+}
+
+impl<'a> Job<'a> {
+    pub fn new(rows: (i32, i32), draw: impl Fn(&mut Band) + Sync + 'a) -> Self {
+        Self { rows, draw: Box::new(draw) }
+    }
+
+    /// Una línea o triángulo ya proyectado, en las filas que cubre
+    pub fn prim(p: Prim) -> Self {
+        Self::new(p.rows(), move |b| p.draw(b))
+    }
+
+    /// Para lo que puede caer en cualquier fila
+    pub fn everywhere(draw: impl Fn(&mut Band) + Sync + 'a) -> Self {
+        Self::new((i32::MIN, i32::MAX), draw)
+    }
+}
+
+/// Hilos que conviene usar: uno por núcleo
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Corre `jobs` en orden sobre franjas horizontales de `fb` repartidas entre
+/// `threads` hilos; cada franja solo corre los trabajos cuyas filas la tocan.
+/// Cada píxel ve los mismos trabajos en el mismo orden con los mismos datos,
+/// así que el frame sale idéntico con cualquier cantidad de hilos.
+pub fn run_banded(fb: &mut FrameBuffer, threads: usize, jobs: &[Job]) {
+    let draw_band = |band: &mut Band| {
+        for job in jobs {
+            if band.clip_rows(job.rows.0, job.rows.1).is_some() {
+                (job.draw)(band);
+            }
+        }
+    };
+
+    if threads <= 1 {
+        draw_band(&mut fb.band());
+        return;
+    }
+
+    let queue = Mutex::new(fb.bands(threads * BANDS_PER_THREAD).into_iter());
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| {
+                loop {
+                    // el lock se suelta antes de dibujar
+                    let next = queue.lock().unwrap().next();
+                    let Some(mut band) = next else { break; };
+                    draw_band(&mut band);
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,78 @@
+use crate::draw::{FrameBuffer, rgb};
+use crate::math::Vec3;
+
+// el brillo se difumina a 1/4 de resolución: más barato y más ancho
+const DOWN: usize = 4;
+// pesos de un gaussiano de 9 muestras (sigma ~2)
+const KERNEL: [f32; 5] = [0.2270, 0.1945, 0.1216, 0.0540, 0.0162];
+
+fn blur_pass(src: &[Vec3], dst: &mut [Vec3], w: usize, h: usize, horizontal: bool) {
+    for y in 0..h {
+        for x in 0..w {
+            let mut acc = src[y * w + x].mul(KERNEL[0]);
+            for (k, &wk) in KERNEL.iter().enumerate().skip(1) {
+                let (a, b) = if horizontal {
+                    (y * w + x.saturating_sub(k), y * w + (x + k).min(w - 1))
+                } else {
+                    (y.saturating_sub(k) * w + x, (y + k).min(h - 1) * w + x)
+                };
+                acc = acc.add(src[a].add(src[b]).mul(wk));
+            }
+            dst[y * w + x] = acc;
+        }
+    }
+}
+
+/// Bloom: difumina el canal de emisión de `fb` (lava, ciudades, la estrella)
+/// y lo suma al color, así lo que brilla sangra un poco sobre lo de al lado.
+pub fn apply_bloom(fb: &mut FrameBuffer, strength: f32) {
+    let (w, h) = (fb.w.div_ceil(DOWN), fb.h.div_ceil(DOWN));
+    if w == 0 || h == 0 { return; }
+
+    // bajar de resolución promediando bloques
+    let mut small = vec![Vec3::new(0.0, 0.0, 0.0); w * h];
+    let mut any = false;
+    for y in 0..fb.h {
+        for x in 0..fb.w {
+            let e = fb.emission[y * fb.w + x];
+            if e.x + e.y + e.z > 0.0 {
+                let i = (y / DOWN) * w + x / DOWN;
+                small[i] = small[i].add(e);
+                any = true;
+            }
+        }
+    }
+    if !any { return; }
+    let inv = 1.0 / (DOWN * DOWN) as f32;
+    for v in small.iter_mut() {
+        *v = v.mul(inv);
+    }
+
+    // dos pasadas gaussianas separables
+    let mut tmp = vec![Vec3::new(0.0, 0.0, 0.0); w * h];
+    for _ in 0..2 {
+        blur_pass(&small, &mut tmp, w, h, true);
+        blur_pass(&tmp, &mut small, w, h, false);
+    }
+
+    // subir con interpolación bilineal y sumar
+    for y in 0..fb.h {
+        let fy = ((y as f32 + 0.5) / DOWN as f32 - 0.5).clamp(0.0, (h - 1) as f32);
+        let (y0, ty) = (fy as usize, fy.fract());
+        let y1 = (y0 + 1).min(h - 1);
+        for x in 0..fb.w {
+            let fx = ((x as f32 + 0.5) / DOWN as f32 - 0.5).clamp(0.0, (w - 1) as f32);
+            let (x0, tx) = (fx as usize, fx.fract());
+            let x1 = (x0 + 1).min(w - 1);
+            let top = Vec3::lerp(small[y0 * w + x0], small[y0 * w + x1], tx);
+            let bot = Vec3::lerp(small[y1 * w + x0], small[y1 * w + x1], tx);
+            let g = Vec3::lerp(top, bot, ty).mul(strength);
+            if g.x + g.y + g.z <= 1e-4 { continue; }
+
+            let i = y * fb.w + x;
+            let c = fb.color[i];
+            let ch = |sh: u32, add: f32| ((((c >> sh) & 0xFF) as f32 / 255.0 + add).clamp(0.0, 1.0) * 255.0) as u8;
+            fb.color[i] = rgb(ch(16, g.x), ch(8, g.y), ch(0, g.z));
+        }
+    }
+}
@@ -1,23 +1,47 @@
 use crate::math::Vec3;
+use crate::bodies::orbit::Orbit;
+use crate::bodies::rings::Rings;
 
-#[derive(Clone)]
+/// Nodo del grafo de escena: orbita alrededor de su padre (o del origen).
+#[derive(Clone, Debug)]
 pub struct Body {
-    pub name: &'static str,
+    pub name: String,
     pub radius: f32,       // radio del planeta
-    pub orbit_r: f32,      // radio orbital (distancia al centro)
-    pub orbit_speed: f32,  // velocidad angular
-    pub rot_speed: f32,    // rotación decorativa
-    pub phase: f32,        // fase inicial
-    pub color: u32,
+    pub mass: f32,         // solo lo usa el modo N-cuerpos
+    pub orbit: Orbit,      // elementos keplerianos respecto al padre
+    pub rot_speed: f32,    // giro sobre su eje (rad/s)
+    pub axial_tilt: f32,   // inclinación del eje respecto al +Y de mundo (rad)
+    pub color: Option<u32>, // color plano del modo rápido (None = el del material)
+    pub seed: u32,         // semilla del ruido de la superficie
+    pub temperature: f32,  // temperatura de cuerpo negro en K (da el color de las estrellas)
     pub draw_orbit: bool,
+    pub rings: Option<Rings>,
+    pub parent: Option<usize>, // índice del padre en `Scene::bodies` (None = raíz)
 }
 
 impl Body {
-    pub fn pos(&self, t: f32) -> Vec3 {
-        if self.orbit_r == 0.0 {
-            return Vec3::new(0.0, 0.0, 0.0); // Sol
-        }
-        let a = self.phase + t * self.orbit_speed;
-        Vec3::new(self.orbit_r * a.cos(), 0.0, self.orbit_r * a.sin())
+    /// Posición en mundo dado dónde está el padre en el tiempo `t`
+    pub fn pos(&self, t: f32, parent_world: Vec3) -> Vec3 {
+        parent_world.add(self.orbit.offset(t))
+    }
+
+    /// Ejes locales del cuerpo en mundo en el tiempo `t` (x, y = eje de giro, z).
+    /// Primero gira `rot_speed * t` sobre Y y después inclina el eje
+    /// `axial_tilt` alrededor del X de mundo.
+    pub fn axes(&self, t: f32) -> (Vec3, Vec3, Vec3) {
+        let (sa, ca) = (self.rot_speed * t).sin_cos();
+        let (st, ct) = self.axial_tilt.sin_cos();
+        let tilt = |v: Vec3| Vec3::new(v.x, v.y * ct - v.z * st, v.y * st + v.z * ct);
+        (
+            tilt(Vec3::new(ca, 0.0, -sa)),
+            tilt(Vec3::new(0.0, 1.0, 0.0)),
+            tilt(Vec3::new(sa, 0.0, ca)),
+        )
+    }
+
+    /// Normal en mundo del plano de los anillos (ecuador + `Rings::tilt`), sin el giro
+    pub fn ring_normal(&self) -> Vec3 {
+        let tilt = self.axial_tilt + self.rings.as_ref().map_or(0.0, |r| r.tilt);
+        Vec3::new(0.0, tilt.cos(), tilt.sin())
     }
 }
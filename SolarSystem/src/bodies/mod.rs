@@ -1,2 +1,3 @@
 pub mod body;
-pub mod moon;
+pub mod orbit;
+pub mod rings;
@@ -0,0 +1,223 @@
+use crate::math::Vec3;
+
+/// Elementos orbitales keplerianos (ángulos en radianes).
+///
+/// El plano de referencia es XZ del mundo (Y hacia arriba); con
+/// excentricidad e inclinación 0 queda el círculo de siempre.
+#[derive(Clone, Copy, Debug)]
+pub struct Orbit {
+    pub semi_major: f32,    // a: semieje mayor (0 = quieto en el padre)
+    pub eccentricity: f32,  // e: 0 círculo, (0,1) elipse
+    pub inclination: f32,   // i: inclinación respecto al plano XZ
+    pub lon_asc_node: f32,  // Ω: longitud del nodo ascendente
+    pub arg_periapsis: f32, // ω: argumento del periapsis
+    pub mean_anomaly: f32,  // M0: anomalía media en t = 0
+    pub mean_motion: f32,   // n: rad/s
+}
+
+impl Orbit {
+    /// Órbita circular en el plano XZ (la de antes: radio, velocidad, fase)
+    pub fn circular(radius: f32, speed: f32, phase: f32) -> Self {
+        Self {
+            semi_major: radius,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            lon_asc_node: 0.0,
+            arg_periapsis: 0.0,
+            mean_anomaly: phase,
+            mean_motion: speed,
+        }
+    }
+
+    /// Sin órbita: el cuerpo se queda en la posición de su padre
+    pub fn fixed() -> Self {
+        Self::circular(0.0, 0.0, 0.0)
+    }
+
+    pub fn periapsis(&self) -> f32 {
+        self.semi_major * (1.0 - self.eccentricity)
+    }
+
+    pub fn apoapsis(&self) -> f32 {
+        self.semi_major * (1.0 + self.eccentricity)
+    }
+
+    /// Resuelve la ecuación de Kepler M = E - e·sin E (Newton)
+    pub fn eccentric_anomaly(&self, mean_anomaly: f32) -> f32 {
+        let e = self.eccentricity;
+        let m = mean_anomaly.rem_euclid(std::f32::consts::TAU);
+        // para e alta arrancar en π converge mejor
+        let mut ea = if e < 0.8 { m } else { std::f32::consts::PI };
+        for _ in 0..12 {
+            let f = ea - e * ea.sin() - m;
+            let d = ea - f / (1.0 - e * ea.cos());
+            if (d - ea).abs() < 1e-6 {
+                return d;
+            }
+            ea = d;
+        }
+        ea
+    }
+
+    /// Punto de la elipse para una anomalía excéntrica E, relativo al foco
+    pub fn point_at_eccentric(&self, ea: f32) -> Vec3 {
+        let a = self.semi_major;
+        let e = self.eccentricity;
+
+        // coordenadas en el plano orbital (x hacia el periapsis)
+        let xp = a * (ea.cos() - e);
+        let yp = a * (1.0 - e * e).max(0.0).sqrt() * ea.sin();
+
+        // ω dentro del plano, luego i alrededor de la línea de nodos, luego Ω
+        let (sw, cw) = self.arg_periapsis.sin_cos();
+        let (si, ci) = self.inclination.sin_cos();
+        let (so, co) = self.lon_asc_node.sin_cos();
+
+        let px = xp * cw - yp * sw;
+        let py = xp * sw + yp * cw;
+
+        let x = px * co - py * ci * so;
+        let y = px * so + py * ci * co;
+        let z = py * si;
+
+        // eclíptica (x, y, z↑) -> mundo (x, y↑, z)
+        Vec3::new(x, z, y)
+    }
+
+    /// Posición relativa al padre en el tiempo `t`
+    pub fn offset(&self, t: f32) -> Vec3 {
+        if self.semi_major == 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+        let m = self.mean_anomaly + self.mean_motion * t;
+        self.point_at_eccentric(self.eccentric_anomaly(m))
+    }
+
+    /// Velocidad kepleriana relativa al padre en `t` si el padre tiene
+    /// parámetro gravitacional `mu` (vis-viva, tangente a la elipse)
+    pub fn velocity(&self, t: f32, mu: f32) -> Vec3 {
+        if self.semi_major == 0.0 || mu <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+        let ea = self.eccentric_anomaly(self.mean_anomaly + self.mean_motion * t);
+        let d = self.point_at_eccentric(ea + 1e-3).sub(self.point_at_eccentric(ea - 1e-3));
+        let dir = if self.mean_motion < 0.0 { d.norm().mul(-1.0) } else { d.norm() };
+        let r = self.point_at_eccentric(ea).len();
+        let speed = (mu * (2.0 / r - 1.0 / self.semi_major)).max(0.0).sqrt();
+        dir.mul(speed)
+    }
+
+    /// Elementos a partir de posición y velocidad relativas al padre (en
+    /// mundo). None si la órbita no es cerrada o es puramente radial.
+    pub fn from_state(r_world: Vec3, v_world: Vec3, mu: f32) -> Option<Orbit> {
+        // mundo (x, y↑, z) -> eclíptica (x, y, z↑)
+        let to_ecl = |p: Vec3| Vec3::new(p.x, p.z, p.y);
+        let r = to_ecl(r_world);
+        let v = to_ecl(v_world);
+
+        let rl = r.len();
+        if rl < 1e-6 || mu <= 0.0 {
+            return None;
+        }
+        let energy = 0.5 * v.dot(v) - mu / rl;
+        if energy >= 0.0 {
+            return None;
+        }
+        let a = -mu / (2.0 * energy);
+
+        let h = r.cross(v);
+        let hl = h.len();
+        if hl < 1e-6 {
+            return None;
+        }
+        let hn = h.mul(1.0 / hl);
+
+        let e_vec = r.mul(v.dot(v) - mu / rl).sub(v.mul(r.dot(v))).mul(1.0 / mu);
+        let e = e_vec.len().min(0.999);
+
+        // línea de nodos; si la órbita está en el plano de referencia se usa +x
+        let node = Vec3::new(-h.y, h.x, 0.0);
+        let (node_dir, lon_asc_node) = if node.len() > 1e-6 * hl {
+            (node.norm(), node.y.atan2(node.x))
+        } else {
+            (Vec3::new(1.0, 0.0, 0.0), 0.0)
+        };
+
+        let p_dir = if e > 1e-6 { e_vec.mul(1.0 / e) } else { node_dir };
+        let q_dir = hn.cross(p_dir);
+        let arg_periapsis = node_dir.cross(p_dir).dot(hn).atan2(node_dir.dot(p_dir));
+
+        let b = a * (1.0 - e * e).sqrt();
+        let ea = (r.dot(q_dir) / b).atan2(r.dot(p_dir) / a + e);
+
+        Some(Orbit {
+            semi_major: a,
+            eccentricity: e,
+            inclination: hn.z.clamp(-1.0, 1.0).acos(),
+            lon_asc_node,
+            arg_periapsis,
+            mean_anomaly: ea - e * ea.sin(),
+            mean_motion: (mu / (a * a * a)).sqrt(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{PI, TAU};
+
+    /// Diferencia entre dos ángulos, en (-π, π]
+    fn angle_diff(a: f32, b: f32) -> f32 {
+        let d = (a - b).rem_euclid(TAU);
+        if d > PI { d - TAU } else { d }
+    }
+
+    #[test]
+    fn kepler_converges() {
+        for &e in &[0.0, 0.5, 0.9, 0.95, 0.99] {
+            let o = Orbit { eccentricity: e, ..Orbit::circular(1.0, 1.0, 0.0) };
+            for k in 0..=64 {
+                let m = k as f32 / 64.0 * TAU;
+                let ea = o.eccentric_anomaly(m);
+                let resid = angle_diff(ea - e * ea.sin(), m);
+                assert!(resid.abs() < 1e-5, "e = {}, M = {}: residuo {}", e, m, resid);
+            }
+        }
+    }
+
+    #[test]
+    fn state_round_trip() {
+        for &e in &[0.0, 0.5, 0.95] {
+            let mu = 50.0;
+            let a = 10.0f32;
+            let orbit = Orbit {
+                semi_major: a,
+                eccentricity: e,
+                inclination: 0.4,
+                lon_asc_node: 1.1,
+                arg_periapsis: 0.7,
+                mean_anomaly: 2.0,
+                mean_motion: (mu / (a * a * a)).sqrt(),
+            };
+            let back = Orbit::from_state(orbit.offset(0.0), orbit.velocity(0.0, mu), mu).unwrap();
+
+            assert!((back.semi_major - a).abs() < 1e-3 * a, "e = {}: a = {}", e, back.semi_major);
+            assert!((back.eccentricity - e).abs() < 1e-3, "e = {}: e = {}", e, back.eccentricity);
+            assert!(angle_diff(back.inclination, 0.4).abs() < 1e-3, "e = {}", e);
+            assert!(angle_diff(back.lon_asc_node, 1.1).abs() < 1e-3, "e = {}", e);
+            if e > 0.0 {
+                // en un círculo ω y M0 no se pueden separar
+                assert!(angle_diff(back.arg_periapsis, 0.7).abs() < 1e-3, "e = {}", e);
+                assert!(angle_diff(back.mean_anomaly, 2.0).abs() < 1e-3, "e = {}", e);
+            }
+            // y recorre la misma elipse
+            let period = TAU / orbit.mean_motion;
+            for k in 0..8 {
+                let t = k as f32 / 8.0 * period;
+                let d = back.offset(t).sub(orbit.offset(t)).len();
+                assert!(d < 1e-3 * a, "e = {}, t = {}: {} de distancia", e, t, d);
+            }
+        }
+    }
+}
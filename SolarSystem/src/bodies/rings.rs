@@ -0,0 +1,57 @@
+use crate::math::Vec3;
+use crate::noise::Noise;
+
+/// Anillos planos alrededor de un cuerpo, en su plano ecuatorial (más `tilt`).
+/// La densidad sale de un perfil radial por tramos multiplicado por anillitos
+/// finos de ruido; `opacity` es la opacidad donde la densidad vale 1.
+#[derive(Clone, Debug)]
+pub struct Rings {
+    pub inner: f32,
+    pub outer: f32,
+    /// inclinación extra sobre el ecuador del cuerpo (rad, alrededor del X de mundo)
+    pub tilt: f32,
+    pub color_inner: Vec3,
+    pub color_outer: Vec3,
+    pub opacity: f32,
+    /// (posición 0..1 de inner a outer, densidad 0..1), ordenado por posición.
+    /// Vacío = densidad 1 en todo el ancho.
+    pub profile: Vec<(f32, f32)>,
+}
+
+impl Rings {
+    /// Posición 0..1 dentro del anillo, o None si `r` cae fuera
+    fn frac(&self, r: f32) -> Option<f32> {
+        if r < self.inner || r > self.outer { return None; }
+        Some((r - self.inner) / (self.outer - self.inner))
+    }
+
+    /// Densidad del perfil a la distancia `r` del centro
+    pub fn density(&self, r: f32) -> f32 {
+        let Some(u) = self.frac(r) else { return 0.0; };
+        let p = &self.profile;
+        if p.is_empty() { return 1.0; }
+        if u <= p[0].0 { return p[0].1; }
+        for w in p.windows(2) {
+            let ((u0, d0), (u1, d1)) = (w[0], w[1]);
+            if u <= u1 {
+                let k = if u1 > u0 { (u - u0) / (u1 - u0) } else { 1.0 };
+                return d0 + (d1 - d0) * k;
+            }
+        }
+        p[p.len() - 1].1
+    }
+
+    /// Opacidad a la distancia `r`, con anillitos finos del ruido del cuerpo
+    pub fn alpha(&self, r: f32, noise: &Noise) -> f32 {
+        let d = self.density(r);
+        if d <= 0.0 { return 0.0; }
+        let ringlets = 0.65 + 0.35 * (noise.fbm(Vec3::new(r * 0.9, 0.5, 0.5), 4, 0.6) * 0.5 + 0.5);
+        (d * ringlets * self.opacity).clamp(0.0, 1.0)
+    }
+
+    /// Color a la distancia `r` (gradiente de adentro hacia afuera)
+    pub fn color(&self, r: f32) -> Vec3 {
+        let u = self.frac(r).unwrap_or(0.0);
+        Vec3::lerp(self.color_inner, self.color_outer, u)
+    }
+}
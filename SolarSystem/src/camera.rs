@@ -1,5 +1,5 @@
 use crate::math::Vec3;
-use crate::window::{WindowCtx, Key};
+use crate::window::{Backend, Key};
 
 pub struct Camera {
     pub pos: Vec3,
@@ -15,6 +15,10 @@ pub struct Camera {
     pub warp_anim_enabled: bool,
 }
 
+impl Default for Camera {
+    fn default() -> Self { Self::new() }
+}
+
 impl Camera {
     pub fn new() -> Self {
         Self {
@@ -42,7 +46,7 @@ impl Camera {
     }
 
     /// Movimiento normal: WASD + Space/Ctrl + flechas
-    pub fn handle_input(&mut self, win: &WindowCtx, dt: f32) {
+    pub fn handle_input(&mut self, win: &dyn Backend, dt: f32) {
         let (right, up, fwd) = self.basis();
         let mut move_dir = Vec3::new(0.0,0.0,0.0);
 
@@ -63,7 +67,7 @@ impl Camera {
     }
 
     /// Maneja teclas 1–5 para hacer warp al Sol/planetas
-    pub fn handle_warp_keys(&mut self, win: &WindowCtx, targets: &[Vec3]) {
+    pub fn handle_warp_keys(&mut self, win: &dyn Backend, targets: &[Vec3]) {
         let keys = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5];
 
         for (idx, key) in keys.iter().enumerate() {
@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+
+use solar_system::bake::DEFAULT_BAKE_WIDTH;
+use solar_system::headless::FrameFormat;
+
+pub const USAGE: &str = "\
+uso: SolarSystem [opciones]
+
+  --system RUTA       sistema a cargar (TOML); por defecto systems/default.toml
+  --nbody             arranca en modo N-cuerpos (se alterna con N)
+  --time-scale X      escala de tiempo inicial (0.1 a 10000, def. 1)
+  --headless N        renderiza N frames sin ventana y los guarda a disco
+  --dt SEG            paso de simulación por frame en headless (def. 0.016)
+  --out RUTA          directorio de frames, o archivo .y4m ('-' = stdout)
+  --format FMT        ppm | png | y4m (def. ppm)
+  --size WxH          resolución (def. 800x480)
+  --bake ANCHO        hornea las superficies estáticas a mapas de ANCHO×ANCHO/2
+  --export-maps DIR   escribe los mapas horneados como PNG en DIR y sale
+  --threads N         hilos para rasterizar y hornear (def. uno por núcleo)
+  -h, --help          muestra esta ayuda";
+
+pub struct HeadlessOpts {
+    pub frames: usize,
+    pub dt: f32,
+    pub out: PathBuf,
+    pub format: FrameFormat,
+}
+
+pub struct Options {
+    pub system: Option<PathBuf>,
+    pub nbody: bool,
+    pub time_scale: f32,
+    pub width: usize,
+    pub height: usize,
+    pub headless: Option<HeadlessOpts>,
+    /// Ancho de los mapas horneados (None = todo procedural)
+    pub bake: Option<usize>,
+    pub export_maps: Option<PathBuf>,
+    /// None = uno por núcleo
+    pub threads: Option<usize>,
+}
+
+fn parse_size(s: &str) -> Option<(usize, usize)> {
+    let (w, h) = s.split_once(['x', 'X'])?;
+    let (w, h) = (w.parse().ok()?, h.parse().ok()?);
+    if w == 0 || h == 0 { return None; }
+    Some((w, h))
+}
+
+/// Parsea los argumentos (sin el nombre del programa)
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut system = None;
+    let mut nbody = false;
+    let mut time_scale = 1.0;
+    let mut width = 800;
+    let mut height = 480;
+    let mut frames = None;
+    let mut dt = 0.016;
+    let mut out = None;
+    let mut format = FrameFormat::Ppm;
+    let mut bake = None;
+    let mut export_maps = None;
+    let mut threads = None;
+
+    let mut it = args.into_iter();
+    while let Some(arg) = it.next() {
+        let mut value = |name: &str| it.next().ok_or(format!("falta el valor de {}", name));
+        match arg.as_str() {
+            "--system" => system = Some(PathBuf::from(value("--system")?)),
+            "--nbody" => nbody = true,
+            "--time-scale" => {
+                let v = value("--time-scale")?;
+                time_scale = v.parse::<f32>().ok().filter(|s| *s > 0.0)
+                    .ok_or(format!("--time-scale: '{}' no es una escala válida", v))?;
+            }
+            "--headless" => {
+                let v = value("--headless")?;
+                frames = Some(v.parse::<usize>().map_err(|_| format!("--headless: '{}' no es un número de frames", v))?);
+            }
+            "--dt" => {
+                let v = value("--dt")?;
+                dt = v.parse::<f32>().ok().filter(|d| *d > 0.0)
+                    .ok_or(format!("--dt: '{}' no es un paso válido", v))?;
+            }
+            "--out" => out = Some(PathBuf::from(value("--out")?)),
+            "--format" => {
+                let v = value("--format")?;
+                format = FrameFormat::parse(&v).ok_or(format!("--format: '{}' no es ppm, png ni y4m", v))?;
+            }
+            "--size" => {
+                let v = value("--size")?;
+                (width, height) = parse_size(&v).ok_or(format!("--size: '{}' no tiene forma WxH", v))?;
+            }
+            "--bake" => {
+                let v = value("--bake")?;
+                bake = Some(v.parse::<usize>().ok().filter(|w| (2..=16384).contains(w))
+                    .ok_or(format!("--bake: '{}' no es un ancho válido (2 a 16384)", v))?);
+            }
+            "--export-maps" => export_maps = Some(PathBuf::from(value("--export-maps")?)),
+            "--threads" => {
+                let v = value("--threads")?;
+                threads = Some(v.parse::<usize>().ok().filter(|n| *n > 0)
+                    .ok_or(format!("--threads: '{}' no es una cantidad de hilos válida", v))?);
+            }
+            "-h" | "--help" => return Err(String::new()),
+            other => return Err(format!("opción desconocida: {}", other)),
+        }
+    }
+
+    let headless = frames.map(|frames| HeadlessOpts {
+        frames,
+        dt,
+        out: out.unwrap_or_else(|| {
+            PathBuf::from(if format == FrameFormat::Y4m { "frames.y4m" } else { "frames" })
+        }),
+        format,
+    });
+
+    // exportar sin --bake usa la resolución por defecto
+    if export_maps.is_some() && bake.is_none() {
+        bake = Some(DEFAULT_BAKE_WIDTH);
+    }
+
+    Ok(Options { system, nbody, time_scale, width, height, headless, bake, export_maps, threads })
+}
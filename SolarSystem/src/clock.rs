@@ -0,0 +1,222 @@
+use std::time::Instant;
+
+/// Escalas de tiempo que recorren `faster` / `slower`
+pub const TIME_SCALES: [f32; 10] = [0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 100.0, 1000.0, 10000.0];
+
+const NORMAL_SCALE: usize = 3;
+
+// un frame largo (ventana arrastrada, breakpoint) no debe teletransportar todo
+const MAX_REAL_DT: f32 = 0.1;
+
+/// Reloj del loop: separa el tiempo real del frame (cámara, animaciones de
+/// UI) del tiempo simulado de los cuerpos, que se puede pausar, acelerar,
+/// invertir o avanzar de a un paso.
+pub struct SimClock {
+    last: Option<Instant>,
+    fixed_dt: Option<f32>,
+    /// Segundos reales del último frame (o el paso fijo en headless)
+    pub real_dt: f32,
+    /// Tiempo simulado actual, el `t` de `Body::pos` y los shaders
+    pub sim_time: f32,
+    /// Cuánto avanzó `sim_time` en el último `tick` (negativo si va en reversa)
+    pub sim_dt: f32,
+    pub paused: bool,
+    pub reversed: bool,
+    scale_idx: usize,
+    pending_steps: u32,
+}
+
+impl Default for SimClock {
+    fn default() -> Self { Self::new() }
+}
+
+impl SimClock {
+    /// Reloj de tiempo real medido con `Instant`
+    pub fn new() -> Self {
+        Self {
+            last: None,
+            fixed_dt: None,
+            real_dt: 0.0,
+            sim_time: 0.0,
+            sim_dt: 0.0,
+            paused: false,
+            reversed: false,
+            scale_idx: NORMAL_SCALE,
+            pending_steps: 0,
+        }
+    }
+
+    /// Reloj de paso fijo (headless): cada `tick` cuenta como `dt` segundos
+    pub fn fixed(dt: f32) -> Self {
+        Self { fixed_dt: Some(dt), ..Self::new() }
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        TIME_SCALES[self.scale_idx]
+    }
+
+    /// Elige la escala de la tabla más cercana a `scale`
+    pub fn set_time_scale(&mut self, scale: f32) {
+        let mut best = 0;
+        for (i, s) in TIME_SCALES.iter().enumerate() {
+            if (s.ln() - scale.ln()).abs() < (TIME_SCALES[best].ln() - scale.ln()).abs() {
+                best = i;
+            }
+        }
+        self.scale_idx = best;
+    }
+
+    pub fn faster(&mut self) {
+        self.scale_idx = (self.scale_idx + 1).min(TIME_SCALES.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.scale_idx = self.scale_idx.saturating_sub(1);
+    }
+
+    pub fn reset_scale(&mut self) {
+        self.scale_idx = NORMAL_SCALE;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn toggle_reverse(&mut self) {
+        self.reversed = !self.reversed;
+    }
+
+    /// Con el reloj en pausa, avanza un frame de simulación en el próximo
+    /// `tick`; andando no hace nada (no quedan pasos guardados para después)
+    pub fn step_once(&mut self) {
+        if self.paused {
+            self.pending_steps += 1;
+        }
+    }
+
+    /// Mide el frame y avanza el tiempo simulado. Llamar una vez por frame.
+    pub fn tick(&mut self) {
+        self.real_dt = match self.fixed_dt {
+            Some(dt) => dt,
+            None => {
+                let now = Instant::now();
+                let dt = self.last.map(|l| (now - l).as_secs_f32()).unwrap_or(0.0);
+                self.last = Some(now);
+                dt.min(MAX_REAL_DT)
+            }
+        };
+
+        let dir = if self.reversed { -1.0 } else { 1.0 };
+        self.sim_dt = if !self.paused {
+            self.real_dt * self.time_scale() * dir
+        } else if self.pending_steps > 0 {
+            self.pending_steps -= 1;
+            // un paso = un frame de 60 fps a la escala actual
+            (1.0 / 60.0) * self.time_scale() * dir
+        } else {
+            0.0
+        };
+        self.sim_time += self.sim_dt;
+    }
+
+    /// Texto corto del estado, p. ej. "x10 reversa (pausa)"
+    pub fn describe(&self) -> String {
+        let mut s = format!("x{}", self.time_scale());
+        if self.reversed {
+            s.push_str(" reversa");
+        }
+        if self.paused {
+            s.push_str(" (pausa)");
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn pause_stops_sim_time() {
+        let mut c = SimClock::fixed(0.5);
+        c.tick();
+        assert_eq!((c.sim_dt, c.sim_time), (0.5, 0.5));
+        c.toggle_pause();
+        c.tick();
+        assert_eq!((c.real_dt, c.sim_dt, c.sim_time), (0.5, 0.0, 0.5));
+        c.toggle_pause();
+        c.tick();
+        assert_eq!(c.sim_time, 1.0);
+    }
+
+    #[test]
+    fn steps_only_while_paused() {
+        let mut c = SimClock::fixed(0.5);
+        // andando, un paso no suma nada ni queda guardado para la pausa
+        c.step_once();
+        c.tick();
+        assert_eq!(c.sim_dt, 0.5);
+        c.toggle_pause();
+        c.tick();
+        assert_eq!(c.sim_dt, 0.0);
+
+        c.step_once();
+        c.step_once();
+        for _ in 0..2 {
+            c.tick();
+            assert_eq!(c.sim_dt, 1.0 / 60.0);
+        }
+        c.tick();
+        assert_eq!(c.sim_dt, 0.0);
+    }
+
+    #[test]
+    fn reverse_flips_the_sign() {
+        let mut c = SimClock::fixed(0.5);
+        c.set_time_scale(10.0);
+        c.toggle_reverse();
+        c.tick();
+        assert_eq!((c.sim_dt, c.sim_time), (-5.0, -5.0));
+        c.toggle_pause();
+        c.step_once();
+        c.tick();
+        assert_eq!(c.sim_dt, -10.0 / 60.0);
+        assert_eq!(c.describe(), "x10 reversa (pausa)");
+    }
+
+    #[test]
+    fn long_frames_are_clamped() {
+        let mut c = SimClock::new();
+        c.tick();
+        assert_eq!(c.real_dt, 0.0, "el primer frame no tiene anterior");
+        c.last = Instant::now().checked_sub(Duration::from_secs(5));
+        c.tick();
+        assert_eq!(c.real_dt, MAX_REAL_DT);
+        assert_eq!(c.sim_time, MAX_REAL_DT);
+        // el paso fijo de headless no se recorta
+        let mut f = SimClock::fixed(1.0);
+        f.tick();
+        assert_eq!(f.real_dt, 1.0);
+    }
+
+    #[test]
+    fn scales_snap_to_the_table() {
+        let mut c = SimClock::new();
+        for (asked, got) in [(1.0, 1.0), (7.0, 5.0), (8.0, 10.0), (300.0, 100.0), (1e9, 10000.0), (1e-3, 0.1)] {
+            c.set_time_scale(asked);
+            assert_eq!(c.time_scale(), got, "pedido {}", asked);
+        }
+        c.set_time_scale(0.1);
+        c.slower();
+        assert_eq!(c.time_scale(), 0.1);
+        c.faster();
+        assert_eq!(c.time_scale(), 0.25);
+        c.set_time_scale(10000.0);
+        c.faster();
+        assert_eq!(c.time_scale(), 10000.0);
+        c.reset_scale();
+        assert_eq!(c.time_scale(), 1.0);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::math::Vec3;
+
 pub const BG: u32 = 0x000000;
 
 #[inline]
@@ -5,6 +7,102 @@ pub fn rgb(r: u8, g: u8, b: u8) -> u32 {
     ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
 }
 
+/// Buffer de color + buffer de profundidad (z de cámara por píxel) + la
+/// parte emitida de cada píxel (RGB lineal, para el bloom)
+pub struct FrameBuffer {
+    pub w: usize,
+    pub h: usize,
+    pub color: Vec<u32>,
+    pub depth: Vec<f32>,
+    pub emission: Vec<Vec3>,
+}
+
+impl FrameBuffer {
+    pub fn new(w: usize, h: usize) -> Self {
+        Self {
+            w,
+            h,
+            color: vec![BG; w * h],
+            depth: vec![f32::INFINITY; w * h],
+            emission: vec![Vec3::new(0.0, 0.0, 0.0); w * h],
+        }
+    }
+
+    pub fn clear(&mut self, c: u32) {
+        self.color.fill(c);
+        self.depth.fill(f32::INFINITY);
+        self.emission.fill(Vec3::new(0.0, 0.0, 0.0));
+    }
+
+    /// Todo el frame como una sola franja
+    pub fn band(&mut self) -> Band<'_> {
+        Band {
+            w: self.w,
+            h: self.h,
+            y0: 0,
+            y1: self.h,
+            color: &mut self.color,
+            depth: &mut self.depth,
+            emission: &mut self.emission,
+        }
+    }
+
+    /// El frame partido en `n` franjas horizontales de alto parecido (menos
+    /// si no hay tantas filas), de arriba a abajo
+    pub fn bands(&mut self, n: usize) -> Vec<Band<'_>> {
+        let (w, h) = (self.w, self.h);
+        let rows = h.div_ceil(n.clamp(1, h.max(1))).max(1);
+        self.color
+            .chunks_mut(rows * w)
+            .zip(self.depth.chunks_mut(rows * w))
+            .zip(self.emission.chunks_mut(rows * w))
+            .enumerate()
+            .map(|(k, ((color, depth), emission))| {
+                let y0 = k * rows;
+                Band { w, h, y0, y1: y0 + color.len() / w, color, depth, emission }
+            })
+            .collect()
+    }
+}
+
+/// Franja de filas `y0..y1` de un `FrameBuffer`, prestada para dibujar. Se
+/// dibuja con coordenadas del frame completo y todo se recorta a la franja,
+/// así cada píxel sale igual sin importar en cuántas franjas se parta el frame.
+pub struct Band<'a> {
+    pub w: usize,
+    /// alto del frame completo
+    pub h: usize,
+    pub y0: usize,
+    pub y1: usize,
+    pub color: &'a mut [u32],
+    pub depth: &'a mut [f32],
+    pub emission: &'a mut [Vec3],
+}
+
+impl Band<'_> {
+    /// Índice del píxel (x, y) de pantalla dentro de la franja (tiene que caer en ella)
+    #[inline]
+    pub fn index(&self, x: i32, y: i32) -> usize {
+        (y as usize - self.y0) * self.w + x as usize
+    }
+
+    /// Recorta las filas `y0..=y1` a las de la franja; None si no se tocan
+    #[inline]
+    pub fn clip_rows(&self, y0: i32, y1: i32) -> Option<(i32, i32)> {
+        let (a, b) = (y0.max(self.y0 as i32), y1.min(self.y1 as i32 - 1));
+        (a <= b).then_some((a, b))
+    }
+
+    /// true si un fragmento en (x,y) a profundidad z quedaría visible
+    #[inline]
+    pub fn depth_test(&self, x: i32, y: i32, z: f32) -> bool {
+        if x < 0 || y < self.y0 as i32 || (x as usize) >= self.w || y >= self.y1 as i32 {
+            return false;
+        }
+        z < self.depth[self.index(x, y)]
+    }
+}
+
 #[inline]
 pub fn put_px(buf: &mut [u32], w: usize, h: usize, x: i32, y: i32, c: u32) {
     if x>=0 && y>=0 && (x as usize) < w && (y as usize) < h {
@@ -12,8 +110,9 @@ pub fn put_px(buf: &mut [u32], w: usize, h: usize, x: i32, y: i32, c: u32) {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn draw_line(buf: &mut [u32], w: usize, h: usize, x0: i32, y0: i32, x1: i32, y1: i32, color: u32) {
-    let (mut x0, mut y0, mut x1, mut y1) = (x0,y0,x1,y1);
+    let (mut x0, mut y0) = (x0,y0);
     let dx = (x1 - x0).abs(); let sx = if x0 < x1 {1} else {-1};
     let dy = -(y1 - y0).abs(); let sy = if y0 < y1 {1} else {-1};
     let mut err = dx + dy;
@@ -38,3 +137,220 @@ pub fn draw_disc(buf: &mut [u32], w: usize, h: usize, cx: i32, cy: i32, r: i32,
         }
     }
 }
+
+// ---------------- Versiones con prueba de profundidad --------------------
+
+/// Pinta un píxel opaco si pasa la prueba de profundidad; tapa lo que
+/// emitía el de atrás (quien emite lo escribe después en `fb.emission`)
+#[inline]
+pub fn put_px_z(fb: &mut Band, x: i32, y: i32, z: f32, c: u32) {
+    if fb.depth_test(x, y, z) {
+        let i = fb.index(x, y);
+        fb.color[i] = c;
+        fb.depth[i] = z;
+        fb.emission[i] = Vec3::new(0.0, 0.0, 0.0);
+    }
+}
+
+/// Línea con z por extremo; se interpola 1/z para que sea correcta en perspectiva.
+/// El paso `i` (0..=pasos, uno por píxel del eje largo) cae en
+/// (x0 + round(dx·i/pasos), y0 + round(dy·i/pasos)); como eso se puede
+/// invertir fila por fila, solo se recorren las filas de la franja y el
+/// resultado no depende de cómo se parta el frame.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_line_z(fb: &mut Band, x0: i32, y0: i32, z0: f32, x1: i32, y1: i32, z1: f32, color: u32) {
+    let Some((ya, yb)) = fb.clip_rows(y0.min(y1), y0.max(y1)) else { return; };
+    let (dx, dy) = ((x1 - x0) as i64, (y1 - y0) as i64);
+    let steps = dx.abs().max(dy.abs()).max(1);
+    let (iz0, iz1) = (1.0 / z0, 1.0 / z1);
+    // round(|a|·i/pasos) con las mitades hacia arriba, con el signo de `a`
+    let offset = |a: i64, i: i64| a.signum() * ((2 * a.abs() * i + steps) / (2 * steps));
+    for y in ya..=yb {
+        let m = (y as i64 - y0 as i64).abs();
+        // pasos i con round(|dy|·i/pasos) = m: (2m-1)·pasos <= 2|dy|·i < (2m+1)·pasos
+        let (lo, hi) = if dy == 0 {
+            (0, steps)
+        } else {
+            let den = 2 * dy.abs();
+            let ceil = |n: i64| n.div_euclid(den) + (n.rem_euclid(den) != 0) as i64;
+            (ceil((2 * m - 1) * steps).max(0), (ceil((2 * m + 1) * steps) - 1).min(steps))
+        };
+        for i in lo..=hi {
+            let z = 1.0 / (iz0 + (iz1 - iz0) * (i as f32 / steps as f32));
+            put_px_z(fb, x0 + offset(dx, i) as i32, y, z, color);
+        }
+    }
+}
+
+/// Disco plano a profundidad constante z
+pub fn draw_disc_z(fb: &mut Band, cx: i32, cy: i32, r: i32, z: f32, color: u32) {
+    if r <= 0 { return; }
+    let Some((ya, yb)) = fb.clip_rows(cy - r, cy + r) else { return; };
+    let r2 = r*r;
+    for dy in (ya - cy)..=(yb - cy) {
+        let yy = cy + dy;
+        let wspan = (r2 - dy*dy).max(0) as f32;
+        let wspan = wspan.sqrt() as i32;
+        for dx in -wspan..=wspan {
+            put_px_z(fb, cx + dx, yy, z, color);
+        }
+    }
+}
+
+#[inline]
+fn edge(a: Vec3, b: Vec3, px: f32, py: f32) -> f32 {
+    (b.x - a.x) * (py - a.y) - (b.y - a.y) * (px - a.x)
+}
+
+/// Triángulo relleno por funciones de borde. Cada vértice es (x, y) en
+/// píxeles y z de cámara; la profundidad se interpola con baricéntricas
+/// sobre 1/z. Acepta ambos sentidos de giro (el culling va por fuera).
+pub fn fill_triangle_z(fb: &mut Band, v0: Vec3, v1: Vec3, v2: Vec3, color: u32) {
+    let area = edge(v0, v1, v2.x, v2.y);
+    if area.abs() < 1e-6 { return; }
+
+    let min_x = v0.x.min(v1.x).min(v2.x).floor().max(0.0) as i32;
+    let max_x = v0.x.max(v1.x).max(v2.x).ceil().min(fb.w as f32 - 1.0) as i32;
+    let min_y = v0.y.min(v1.y).min(v2.y).floor().max(0.0) as i32;
+    let max_y = v0.y.max(v1.y).max(v2.y).ceil().min(fb.h as f32 - 1.0) as i32;
+    if min_x > max_x { return; }
+    let Some((min_y, max_y)) = fb.clip_rows(min_y, max_y) else { return; };
+
+    let inv_area = 1.0 / area;
+    let (iz0, iz1, iz2) = (1.0 / v0.z, 1.0 / v1.z, 1.0 / v2.z);
+
+    for y in min_y..=max_y {
+        let py = y as f32 + 0.5;
+        for x in min_x..=max_x {
+            let px = x as f32 + 0.5;
+
+            // baricéntricas normalizadas (positivas dentro sin importar el giro)
+            let b0 = edge(v1, v2, px, py) * inv_area;
+            let b1 = edge(v2, v0, px, py) * inv_area;
+            let b2 = edge(v0, v1, px, py) * inv_area;
+            if b0 < 0.0 || b1 < 0.0 || b2 < 0.0 { continue; }
+
+            let z = 1.0 / (b0 * iz0 + b1 * iz1 + b2 * iz2);
+            put_px_z(fb, x, y, z, color);
+        }
+    }
+}
+
+/// Escala un color 0xRRGGBB por un factor (sombreado plano)
+#[inline]
+pub fn scale_rgb(c: u32, k: f32) -> u32 {
+    let ch = |s: u32| (((c >> s) & 0xFF) as f32 * k).clamp(0.0, 255.0) as u8;
+    rgb(ch(16), ch(8), ch(0))
+}
+
+// ---------------- Primitivas ya proyectadas --------------------
+
+/// Línea o triángulo en coordenadas de pantalla (z de cámara por vértice),
+/// listo para rasterizar en cualquier franja
+#[derive(Clone, Copy, Debug)]
+pub enum Prim {
+    Line { a: (i32, i32, f32), b: (i32, i32, f32), color: u32 },
+    Triangle { v: [Vec3; 3], color: u32 },
+}
+
+impl Prim {
+    /// Línea entre dos puntos de pantalla (x, y subpíxel, z de cámara)
+    /// recortada al rectángulo de `w`×`h`, para no recorrer kilómetros de
+    /// línea fuera de pantalla cuando un extremo se proyecta lejísimos. La z
+    /// del corte se interpola en 1/z. None si no toca la pantalla.
+    pub fn line(a: Vec3, b: Vec3, color: u32, w: usize, h: usize) -> Option<Prim> {
+        // Liang–Barsky contra 0 <= x < w, 0 <= y < h (justo por dentro, para
+        // que al truncar quede en el último píxel)
+        let (xmax, ymax) = (w as f32 - 1e-3, h as f32 - 1e-3);
+        let d = b.sub(a);
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+        for (p, q) in [
+            (-d.x, a.x),
+            (d.x, xmax - a.x),
+            (-d.y, a.y),
+            (d.y, ymax - a.y),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 { return None; }
+            } else {
+                let r = q / p;
+                if p < 0.0 { t0 = t0.max(r); } else { t1 = t1.min(r); }
+            }
+        }
+        if t0 > t1 || !t0.is_finite() || !t1.is_finite() { return None; }
+        let at = |t: f32| {
+            let iz = 1.0 / a.z + (1.0 / b.z - 1.0 / a.z) * t;
+            (
+                (a.x + d.x * t) as i32,
+                (a.y + d.y * t) as i32,
+                1.0 / iz,
+            )
+        };
+        Some(Prim::Line { a: at(t0), b: at(t1), color })
+    }
+
+    /// Filas de pantalla que puede tocar (inclusive)
+    pub fn rows(&self) -> (i32, i32) {
+        match *self {
+            Prim::Line { a, b, .. } => (a.1.min(b.1), a.1.max(b.1)),
+            Prim::Triangle { v, .. } => (
+                v[0].y.min(v[1].y).min(v[2].y).floor() as i32,
+                v[0].y.max(v[1].y).max(v[2].y).ceil() as i32,
+            ),
+        }
+    }
+
+    pub fn draw(&self, fb: &mut Band) {
+        match *self {
+            Prim::Line { a, b, color } => draw_line_z(fb, a.0, a.1, a.2, b.0, b.1, b.2, color),
+            Prim::Triangle { v, color } => fill_triangle_z(fb, v[0], v[1], v[2], color),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(fb: &FrameBuffer) -> Vec<(usize, usize)> {
+        (0..fb.w * fb.h).filter(|&i| fb.color[i] != BG).map(|i| (i % fb.w, i / fb.w)).collect()
+    }
+
+    #[test]
+    fn line_is_connected_and_band_independent() {
+        let lines = [(2, 3, 37, 20), (37, 20, 2, 3), (5, 29, 6, 0), (0, 10, 39, 10), (20, 0, 20, 29), (3, 3, 3, 3)];
+        for (x0, y0, x1, y1) in lines {
+            let mut whole = FrameBuffer::new(40, 30);
+            draw_line_z(&mut whole.band(), x0, y0, 5.0, x1, y1, 9.0, 0xFFFFFF);
+            let px = lit(&whole);
+            let n = (x1 - x0).abs().max((y1 - y0).abs()) as usize + 1;
+            assert_eq!(px.len(), n, "un píxel por paso en {:?}", (x0, y0, x1, y1));
+            assert!(px.contains(&(x0 as usize, y0 as usize)) && px.contains(&(x1 as usize, y1 as usize)));
+
+            for k in [2, 3, 7] {
+                let mut split = FrameBuffer::new(40, 30);
+                for mut b in split.bands(k) {
+                    draw_line_z(&mut b, x0, y0, 5.0, x1, y1, 9.0, 0xFFFFFF);
+                }
+                assert!(split.color == whole.color && split.depth == whole.depth);
+            }
+        }
+    }
+
+    #[test]
+    fn line_prim_is_clipped_to_screen() {
+        // un extremo casi en el plano cercano se proyecta a millones de píxeles
+        let a = Vec3::new(20.0, 15.0, 10.0);
+        let b = Vec3::new(3.0e6, -2.0e6, 0.01);
+        let Some(Prim::Line { a: pa, b: pb, .. }) = Prim::line(a, b, 0xFFFFFF, 40, 30) else {
+            panic!("la línea cruza la pantalla");
+        };
+        for (x, y, z) in [pa, pb] {
+            assert!((0..40).contains(&x) && (0..30).contains(&y), "{:?} fuera de pantalla", (x, y));
+            assert!((0.01..=10.0).contains(&z));
+        }
+        assert_eq!(pa, (20, 15, 10.0));
+
+        assert!(Prim::line(Vec3::new(-5.0, 2.0, 1.0), Vec3::new(-1.0, 50.0, 1.0), 0, 40, 30).is_none());
+    }
+}
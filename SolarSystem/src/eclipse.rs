@@ -0,0 +1,107 @@
+use std::f32::consts::PI;
+
+use crate::math::Vec3;
+
+/// Esfera que puede tapar al Sol (mismo espacio que el punto que se prueba)
+#[derive(Clone, Copy, Debug)]
+pub struct Occluder {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+/// Área de la intersección de dos discos de radios `a` y `b` a distancia `d`
+fn disc_overlap(a: f32, b: f32, d: f32) -> f32 {
+    if d >= a + b { return 0.0; }
+    if d <= (a - b).abs() {
+        let m = a.min(b);
+        return PI * m * m;
+    }
+    let ca = ((d * d + a * a - b * b) / (2.0 * d * a)).clamp(-1.0, 1.0);
+    let cb = ((d * d + b * b - a * a) / (2.0 * d * b)).clamp(-1.0, 1.0);
+    let k = ((-d + a + b) * (d + a - b) * (d - a + b) * (d + a + b)).max(0.0);
+    a * a * ca.acos() + b * b * cb.acos() - 0.5 * k.sqrt()
+}
+
+/// Fracción visible (0..1) del disco del Sol desde `p`. Cada oclusor se ve
+/// como un disco de radio angular propio; lo que tapa de la estrella da la
+/// sombra, y como la estrella tiene tamaño aparente sale la penumbra sola.
+/// Las tapadas de varios oclusores se suman (el solape entre ellos se ignora).
+pub fn sun_visibility(p: Vec3, sun_center: Vec3, sun_radius: f32, occluders: &[Occluder]) -> f32 {
+    let to_sun = sun_center.sub(p);
+    let sun_dist = to_sun.len();
+    if sun_dist <= sun_radius { return 1.0; }
+    let sun_dir = to_sun.mul(1.0 / sun_dist);
+    let a = (sun_radius / sun_dist).asin();
+    let sun_area = PI * a * a;
+
+    let mut covered = 0.0;
+    for o in occluders {
+        let to_o = o.center.sub(p);
+        let along = to_o.dot(sun_dir);
+        // detrás del punto o más allá del Sol no tapa nada
+        if along <= 0.0 || along - o.radius > sun_dist { continue; }
+        let dist = to_o.len();
+        if dist <= o.radius { continue; }
+        let b = (o.radius / dist).asin();
+        let theta = (to_o.mul(1.0 / dist).dot(sun_dir)).clamp(-1.0, 1.0).acos();
+        if theta >= a + b { continue; }
+        covered += disc_overlap(a, b, theta);
+    }
+    (1.0 - covered / sun_area).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32, eps: f32) -> bool {
+        (a - b).abs() <= eps
+    }
+
+    #[test]
+    fn overlap_area() {
+        assert_eq!(disc_overlap(1.0, 1.0, 2.5), 0.0);
+        // uno adentro del otro: el área del chico
+        assert!(close(disc_overlap(2.0, 0.5, 0.3), PI * 0.25, 1e-6));
+        // dos círculos unitarios a distancia 1: 2π/3 - √3/2
+        assert!(close(disc_overlap(1.0, 1.0, 1.0), 2.0 * PI / 3.0 - 0.75f32.sqrt(), 1e-4));
+    }
+
+    // Sol de radio 1 a 100 unidades, visto desde el origen
+    const SUN: Vec3 = Vec3 { x: 0.0, y: 0.0, z: 100.0 };
+
+    fn vis(occluders: &[Occluder]) -> f32 {
+        sun_visibility(Vec3::new(0.0, 0.0, 0.0), SUN, 1.0, occluders)
+    }
+
+    #[test]
+    fn no_overlap() {
+        assert_eq!(vis(&[]), 1.0);
+        // al costado
+        assert_eq!(vis(&[Occluder { center: Vec3::new(10.0, 0.0, 50.0), radius: 1.0 }]), 1.0);
+        // detrás del punto
+        assert_eq!(vis(&[Occluder { center: Vec3::new(0.0, 0.0, -50.0), radius: 5.0 }]), 1.0);
+    }
+
+    #[test]
+    fn total_eclipse() {
+        // el doble de tamaño aparente, centrado
+        assert_eq!(vis(&[Occluder { center: Vec3::new(0.0, 0.0, 50.0), radius: 1.0 }]), 0.0);
+    }
+
+    #[test]
+    fn annular_eclipse() {
+        // la mitad de tamaño aparente, centrado: tapa un cuarto del disco
+        let v = vis(&[Occluder { center: Vec3::new(0.0, 0.0, 50.0), radius: 0.25 }]);
+        assert!(close(v, 0.75, 1e-3), "{}", v);
+    }
+
+    #[test]
+    fn half_covered() {
+        // un oclusor enorme con el borde justo sobre el centro del Sol
+        let b = 0.3f32;
+        let center = Vec3::new(b.sin(), 0.0, b.cos()).mul(10.0);
+        let v = vis(&[Occluder { center, radius: 10.0 * b.sin() }]);
+        assert!(close(v, 0.5, 0.01), "{}", v);
+    }
+}
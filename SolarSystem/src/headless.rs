@@ -0,0 +1,104 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::image_io::{Y4mWriter, write_png, write_ppm};
+use crate::window::{Backend, Key};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FrameFormat {
+    Ppm,
+    Png,
+    Y4m,
+}
+
+impl FrameFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png),
+            "y4m" => Some(Self::Y4m),
+            _ => None,
+        }
+    }
+}
+
+enum Sink {
+    /// Un archivo por frame dentro de un directorio
+    Files { dir: PathBuf, format: FrameFormat },
+    /// Un solo stream de video (archivo o stdout con "-")
+    Video(Y4mWriter),
+}
+
+/// Backend sin ventana: corre `frames` frames a paso fijo `dt` y los escribe
+/// a disco. No necesita display.
+pub struct HeadlessCtx {
+    width: usize,
+    height: usize,
+    frames: usize,
+    frame_idx: usize,
+    dt: f32,
+    sink: Sink,
+}
+
+impl HeadlessCtx {
+    pub fn new(
+        width: usize,
+        height: usize,
+        frames: usize,
+        dt: f32,
+        out: PathBuf,
+        format: FrameFormat,
+    ) -> io::Result<Self> {
+        let sink = match format {
+            FrameFormat::Y4m => {
+                let writer: Box<dyn io::Write> = if out.as_os_str() == "-" {
+                    Box::new(io::stdout().lock())
+                } else {
+                    Box::new(io::BufWriter::new(fs::File::create(&out)?))
+                };
+                Sink::Video(Y4mWriter::new(writer, width, height, 1.0 / dt)?)
+            }
+            _ => {
+                fs::create_dir_all(&out)?;
+                Sink::Files { dir: out, format }
+            }
+        };
+
+        Ok(Self { width, height, frames, frame_idx: 0, dt, sink })
+    }
+
+    fn write_frame(&mut self, buf: &[u32]) -> io::Result<()> {
+        let (w, h) = (self.width, self.height);
+        match &mut self.sink {
+            Sink::Files { dir, format } => {
+                let ext = if *format == FrameFormat::Png { "png" } else { "ppm" };
+                let path = dir.join(format!("frame_{:05}.{}", self.frame_idx, ext));
+                match format {
+                    FrameFormat::Png => write_png(&path, w, h, buf),
+                    _ => write_ppm(&path, w, h, buf),
+                }
+            }
+            Sink::Video(y4m) => y4m.write_frame(buf),
+        }
+    }
+}
+
+impl Backend for HeadlessCtx {
+    fn size(&self) -> (usize, usize) { (self.width, self.height) }
+
+    fn is_open(&self) -> bool { self.frame_idx < self.frames }
+
+    // sin teclado: la cámara se queda en su posición inicial
+    fn key_down(&self, _k: Key) -> bool { false }
+
+    fn key_pressed(&self, _k: Key) -> bool { false }
+
+    fn present(&mut self, buf: &[u32]) -> io::Result<()> {
+        self.write_frame(buf)?;
+        self.frame_idx += 1;
+        Ok(())
+    }
+
+    fn fixed_dt(&self) -> Option<f32> { Some(self.dt) }
+}
@@ -0,0 +1,626 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::inflate::zlib_decompress;
+
+// Escritores de imágenes sin dependencias: PPM (P6), PNG sin comprimir y Y4M,
+// y lectores de PPM, PNG y TGA para las texturas.
+// El buffer siempre es 0xRRGGBB por píxel, igual que el FrameBuffer.
+
+#[inline]
+fn split_rgb(c: u32) -> (u8, u8, u8) {
+    ((c >> 16) as u8, (c >> 8) as u8, c as u8)
+}
+
+fn rgb_bytes(buf: &[u32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len() * 3);
+    for &c in buf {
+        let (r, g, b) = split_rgb(c);
+        out.extend_from_slice(&[r, g, b]);
+    }
+    out
+}
+
+pub fn write_ppm(path: &Path, w: usize, h: usize, buf: &[u32]) -> io::Result<()> {
+    let mut f = BufWriter::new(File::create(path)?);
+    writeln!(f, "P6\n{} {}\n255", w, h)?;
+    f.write_all(&rgb_bytes(buf))?;
+    f.flush()
+}
+
+// ---------------- PNG (deflate en bloques "stored") --------------------
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &x in data {
+        a = (a + x as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+pub fn write_png(path: &Path, w: usize, h: usize, buf: &[u32]) -> io::Result<()> {
+    // filas con byte de filtro 0 + RGB
+    let mut raw = Vec::with_capacity(h * (w * 3 + 1));
+    for row in buf.chunks(w).take(h) {
+        raw.push(0);
+        raw.extend_from_slice(&rgb_bytes(row));
+    }
+
+    // zlib con bloques sin compresión de hasta 65535 bytes
+    let mut z = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(65535).peekable();
+    if blocks.peek().is_none() {
+        z.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        z.push(last as u8);
+        z.extend_from_slice(&len.to_le_bytes());
+        z.extend_from_slice(&(!len).to_le_bytes());
+        z.extend_from_slice(block);
+    }
+    z.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(w as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(h as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // 8 bits, RGB
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    png_chunk(&mut out, b"IHDR", &ihdr);
+    png_chunk(&mut out, b"IDAT", &z);
+    png_chunk(&mut out, b"IEND", &[]);
+
+    let mut f = File::create(path)?;
+    f.write_all(&out)
+}
+
+// ---------------- Y4M (video YUV 4:4:4 sin comprimir) --------------------
+
+/// Cuadros por segundo como fracción reducida, redondeados a milésimas
+fn fps_ratio(fps: f32) -> (u64, u64) {
+    let gcd = |mut a: u64, mut b: u64| {
+        while b != 0 { (a, b) = (b, a % b); }
+        a
+    };
+    let num = ((fps as f64 * 1000.0).round() as u64).max(1);
+    let g = gcd(num, 1000);
+    (num / g, 1000 / g)
+}
+
+pub struct Y4mWriter {
+    out: Box<dyn Write>,
+    w: usize,
+    h: usize,
+}
+
+impl Y4mWriter {
+    /// `fps` va como fracción exacta a milésimas (62.5 → 125:2); la cabecera
+    /// se escribe de una vez
+    pub fn new(mut out: Box<dyn Write>, w: usize, h: usize, fps: f32) -> io::Result<Self> {
+        let (num, den) = fps_ratio(fps);
+        writeln!(out, "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C444", w, h, num, den)?;
+        Ok(Self { out, w, h })
+    }
+
+    pub fn write_frame(&mut self, buf: &[u32]) -> io::Result<()> {
+        let n = self.w * self.h;
+        let mut planes = vec![0u8; n * 3];
+        for (i, &c) in buf.iter().take(n).enumerate() {
+            let (r, g, b) = split_rgb(c);
+            let (r, g, b) = (r as f32, g as f32, b as f32);
+            // BT.601 rango limitado
+            let y = 16.0 + 0.257 * r + 0.504 * g + 0.098 * b;
+            let u = 128.0 - 0.148 * r - 0.291 * g + 0.439 * b;
+            let v = 128.0 + 0.439 * r - 0.368 * g - 0.071 * b;
+            planes[i] = y.round().clamp(0.0, 255.0) as u8;
+            planes[n + i] = u.round().clamp(0.0, 255.0) as u8;
+            planes[2 * n + i] = v.round().clamp(0.0, 255.0) as u8;
+        }
+        self.out.write_all(b"FRAME\n")?;
+        self.out.write_all(&planes)?;
+        self.out.flush()
+    }
+}
+
+// ---------------- Lectura --------------------
+
+/// Imagen leída de disco, 0xRRGGBB por píxel y fila a fila desde arriba
+/// (el alfa se descarta)
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub w: usize,
+    pub h: usize,
+    pub pixels: Vec<u32>,
+}
+
+fn bad(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[inline]
+fn pack(r: u8, g: u8, b: u8) -> u32 {
+    ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+}
+
+/// Lee un PPM (P3/P5/P6), PNG o TGA; el formato sale de los primeros bytes y,
+/// para el TGA que no tiene firma, de la extensión
+pub fn read_image(path: &Path) -> io::Result<Image> {
+    let data = std::fs::read(path)?;
+    let is_tga = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("tga"));
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        read_png(&data)
+    } else if data.len() > 1 && data[0] == b'P' && matches!(data[1], b'3' | b'5' | b'6') {
+        read_ppm(&data)
+    } else if is_tga {
+        read_tga(&data)
+    } else {
+        Err(bad("formato de imagen desconocido (se aceptan PPM, PNG y TGA)"))
+    }
+}
+
+/// PPM/PGM: P6 (RGB binario), P5 (gris binario) y P3 (RGB en texto), hasta
+/// 16 bits por canal
+pub fn read_ppm(data: &[u8]) -> io::Result<Image> {
+    if data.len() < 2 || data[0] != b'P' { return Err(bad("PPM: cabecera inválida")); }
+    // cabecera: palabras separadas por blancos, con comentarios desde '#'
+    let mut pos = 2;
+    let token = |pos: &mut usize| -> io::Result<usize> {
+        loop {
+            while *pos < data.len() && data[*pos].is_ascii_whitespace() { *pos += 1; }
+            if *pos < data.len() && data[*pos] == b'#' {
+                while *pos < data.len() && data[*pos] != b'\n' { *pos += 1; }
+            } else {
+                break;
+            }
+        }
+        let start = *pos;
+        while *pos < data.len() && data[*pos].is_ascii_digit() { *pos += 1; }
+        std::str::from_utf8(&data[start..*pos])
+            .ok()
+            .and_then(|t| t.parse().ok())
+            .ok_or_else(|| bad("PPM: cabecera inválida"))
+    };
+    let magic = data[1];
+    if !matches!(magic, b'3' | b'5' | b'6') { return Err(bad("PPM: solo se aceptan P3, P5 y P6")); }
+    let w = token(&mut pos)?;
+    let h = token(&mut pos)?;
+    let maxval = token(&mut pos)?;
+    if w == 0 || h == 0 || maxval == 0 || maxval > 65535 {
+        return Err(bad("PPM: cabecera inválida"));
+    }
+    let scale = |v: usize| (v.min(maxval) * 255 / maxval) as u8;
+    let channels = if magic == b'5' { 1 } else { 3 };
+    let bytes = if maxval > 255 { 2 } else { 1 };
+    let cut = || bad("PPM: datos cortados");
+    // una cabecera enorme no puede pedir más muestras que bytes hay
+    let n = w.checked_mul(h).and_then(|n| n.checked_mul(channels)).ok_or_else(cut)?;
+    if n > data.len() { return Err(cut()); }
+
+    let mut samples = Vec::with_capacity(n);
+    if magic == b'3' {
+        for _ in 0..n {
+            samples.push(scale(token(&mut pos)?));
+        }
+    } else {
+        // un solo blanco separa la cabecera de los datos
+        pos += 1;
+        let body = data.get(pos..pos + n * bytes).ok_or_else(cut)?;
+        for s in body.chunks(bytes) {
+            let v = if bytes == 2 { (s[0] as usize) << 8 | s[1] as usize } else { s[0] as usize };
+            samples.push(scale(v));
+        }
+    }
+
+    let pixels = samples
+        .chunks(channels)
+        .map(|c| if channels == 1 { pack(c[0], c[0], c[0]) } else { pack(c[0], c[1], c[2]) })
+        .collect();
+    Ok(Image { w, h, pixels })
+}
+
+/// TGA sin paleta: color verdadero (2) o gris (3), sin comprimir o con RLE
+/// (10, 11), de 8, 16, 24 o 32 bits
+pub fn read_tga(data: &[u8]) -> io::Result<Image> {
+    if data.len() < 18 { return Err(bad("TGA: cabecera cortada")); }
+    let id_len = data[0] as usize;
+    let cmap_type = data[1];
+    let kind = data[2];
+    let cmap_len = u16::from_le_bytes([data[5], data[6]]) as usize;
+    let cmap_bits = data[7] as usize;
+    let w = u16::from_le_bytes([data[12], data[13]]) as usize;
+    let h = u16::from_le_bytes([data[14], data[15]]) as usize;
+    let bpp = data[16] as usize;
+    let top_down = data[17] & 0x20 != 0;
+    let right_left = data[17] & 0x10 != 0;
+
+    let (rle, gray) = match kind {
+        2 => (false, false),
+        3 => (false, true),
+        10 => (true, false),
+        11 => (true, true),
+        _ => return Err(bad("TGA: solo se aceptan imágenes sin paleta")),
+    };
+    let bytes = bpp / 8;
+    let ok_depth = if gray { bpp == 8 } else { matches!(bpp, 16 | 24 | 32) };
+    if !ok_depth || w == 0 || h == 0 { return Err(bad("TGA: profundidad no soportada")); }
+
+    let decode = |p: &[u8]| -> u32 {
+        match bytes {
+            1 => pack(p[0], p[0], p[0]),
+            2 => {
+                // 5 bits por canal: A RRRRR GGGGG BBBBB
+                let v = u16::from_le_bytes([p[0], p[1]]);
+                let c = |s: u16| (((v >> s) & 31) * 255 / 31) as u8;
+                pack(c(10), c(5), c(0))
+            }
+            _ => pack(p[2], p[1], p[0]),
+        }
+    };
+
+    let mut pos = 18 + id_len + if cmap_type == 1 { cmap_len * cmap_bits.div_ceil(8) } else { 0 };
+    let n = w * h;
+    // la cabecera puede mentir: no reservar más de lo que los datos pueden dar
+    let mut pixels = Vec::with_capacity(n.min(data.len()));
+    let cut = || bad("TGA: datos cortados");
+    while pixels.len() < n {
+        if !rle {
+            let p = data.get(pos..pos + bytes).ok_or_else(cut)?;
+            pixels.push(decode(p));
+            pos += bytes;
+            continue;
+        }
+        let head = *data.get(pos).ok_or_else(cut)? as usize;
+        pos += 1;
+        let count = (head & 0x7F) + 1;
+        if head & 0x80 != 0 {
+            let c = decode(data.get(pos..pos + bytes).ok_or_else(cut)?);
+            pos += bytes;
+            pixels.extend(std::iter::repeat_n(c, count));
+        } else {
+            for _ in 0..count {
+                pixels.push(decode(data.get(pos..pos + bytes).ok_or_else(cut)?));
+                pos += bytes;
+            }
+        }
+    }
+    pixels.truncate(n);
+
+    // el origen por defecto es abajo a la izquierda
+    if !top_down {
+        let rows: Vec<&[u32]> = pixels.chunks(w).rev().collect();
+        pixels = rows.concat();
+    }
+    if right_left {
+        for row in pixels.chunks_mut(w) { row.reverse(); }
+    }
+    Ok(Image { w, h, pixels })
+}
+
+/// PNG no entrelazado de cualquier tipo de color y profundidad
+pub fn read_png(data: &[u8]) -> io::Result<Image> {
+    let mut pos = 8;
+    let (mut w, mut h, mut depth, mut color) = (0, 0, 0u8, 0u8);
+    let mut palette: Vec<u32> = Vec::new();
+    let mut idat = Vec::new();
+    loop {
+        let head = data.get(pos..pos + 8).ok_or_else(|| bad("PNG: datos cortados"))?;
+        let len = u32::from_be_bytes([head[0], head[1], head[2], head[3]]) as usize;
+        let kind = &head[4..8];
+        let body = data.get(pos + 8..pos + 8 + len).ok_or_else(|| bad("PNG: datos cortados"))?;
+        pos += 12 + len;
+        match kind {
+            b"IHDR" => {
+                if len < 13 { return Err(bad("PNG: IHDR inválido")); }
+                w = u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize;
+                h = u32::from_be_bytes([body[4], body[5], body[6], body[7]]) as usize;
+                depth = body[8];
+                color = body[9];
+                if body[12] != 0 { return Err(bad("PNG: entrelazado no soportado")); }
+            }
+            b"PLTE" => {
+                palette = body.chunks_exact(3).map(|c| pack(c[0], c[1], c[2])).collect();
+            }
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+    let channels = match color {
+        0 | 3 => 1,
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => return Err(bad("PNG: tipo de color inválido")),
+    };
+    if w == 0 || h == 0 || !matches!(depth, 1 | 2 | 4 | 8 | 16) {
+        return Err(bad("PNG: cabecera inválida"));
+    }
+    if color == 3 && palette.is_empty() { return Err(bad("PNG: falta la paleta")); }
+
+    let raw = zlib_decompress(&idat)?;
+    let bits = channels * depth as usize;
+    // con una cabecera absurda las cuentas se pasan: eso es un PNG cortado
+    let cut = || bad("PNG: datos cortados");
+    let stride = w.checked_mul(bits).ok_or_else(cut)?.div_ceil(8);
+    // distancia al píxel de la izquierda para los filtros (al menos un byte)
+    let bpp = bits.div_ceil(8);
+    let size = (stride + 1).checked_mul(h).ok_or_else(cut)?;
+    if raw.len() < size { return Err(cut()); }
+
+    let mut prev = vec![0u8; stride];
+    let mut row = vec![0u8; stride];
+    let mut pixels = Vec::with_capacity(w * h);
+    for y in 0..h {
+        let line = &raw[y * (stride + 1)..(y + 1) * (stride + 1)];
+        row.copy_from_slice(&line[1..]);
+        unfilter(line[0], &mut row, &prev, bpp)?;
+
+        // muestra `c` del píxel `x`, llevada a 8 bits (las paletas quedan en índice)
+        let sample = |x: usize, c: usize| -> u8 {
+            let i = x * channels + c;
+            match depth {
+                8 => row[i],
+                16 => row[i * 2],
+                d => {
+                    let d = d as usize;
+                    let bit = i * d;
+                    let v = (row[bit / 8] >> (8 - d - bit % 8)) & ((1 << d) - 1) as u8;
+                    if color == 3 { v } else { (v as usize * 255 / ((1 << d) - 1)) as u8 }
+                }
+            }
+        };
+        for x in 0..w {
+            pixels.push(match color {
+                0 | 4 => {
+                    let g = sample(x, 0);
+                    pack(g, g, g)
+                }
+                3 => *palette.get(sample(x, 0) as usize).ok_or_else(|| bad("PNG: índice fuera de la paleta"))?,
+                _ => pack(sample(x, 0), sample(x, 1), sample(x, 2)),
+            });
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+    Ok(Image { w, h, pixels })
+}
+
+/// Deshace el filtro de una fila de PNG en el lugar; `prev` es la fila de
+/// arriba ya reconstruida
+fn unfilter(kind: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> io::Result<()> {
+    for i in 0..row.len() {
+        let a = if i >= bpp { row[i - bpp] } else { 0 };
+        let b = prev[i];
+        let c = if i >= bpp { prev[i - bpp] } else { 0 };
+        let pred = match kind {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            4 => {
+                let p = a as i16 + b as i16 - c as i16;
+                let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+                if pa <= pb && pa <= pc { a } else if pb <= pc { b } else { c }
+            }
+            _ => return Err(bad("PNG: filtro inválido")),
+        };
+        row[i] = row[i].wrapping_add(pred);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 4×5 RGB de 8 bits, fila y con el filtro y (0 a 4), IDAT partido en dos
+    const PNG_RGB8: [u8; 136] = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d,
+        0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x05,
+        0x08, 0x02, 0x00, 0x00, 0x00, 0xed, 0xcf, 0xda, 0x8c, 0x00, 0x00, 0x00,
+        0x21, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x60, 0x60, 0xf8, 0x6f,
+        0xc4, 0xf0, 0x3a, 0x85, 0xe1, 0xfa, 0x34, 0x86, 0xc3, 0x8c, 0xec, 0x0c,
+        0x0f, 0x8d, 0x78, 0xdf, 0x40, 0x10, 0x13, 0x3b, 0xc3, 0x23, 0x76, 0xde,
+        0x47, 0xec, 0x73, 0x48, 0x4b, 0xb5, 0x00, 0x00, 0x00, 0x22, 0x49, 0x44,
+        0x41, 0x54, 0x52, 0x8f, 0xd8, 0xd5, 0x1f, 0x31, 0xf3, 0x31, 0xb8, 0xc8,
+        0x4a, 0x3d, 0x97, 0x55, 0x7c, 0x2e, 0xab, 0xfe, 0x9c, 0x05, 0x2c, 0xf3,
+        0x86, 0x5d, 0xea, 0x0d, 0xbb, 0xfa, 0x1b, 0x00, 0xa8, 0x9e, 0x14, 0xe6,
+        0x24, 0xce, 0xab, 0x15, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44,
+        0xae, 0x42, 0x60, 0x82,
+    ];
+    // 3×2 RGB de 16 bits (filtros Paeth y Up)
+    const PNG_RGB16: [u8; 98] = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d,
+        0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x02,
+        0x10, 0x02, 0x00, 0x00, 0x00, 0x42, 0x86, 0x2d, 0x0e, 0x00, 0x00, 0x00,
+        0x0e, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x61, 0x60, 0x60, 0xd4,
+        0x61, 0x8a, 0x60, 0x7e, 0xc1, 0x02, 0x86, 0x22, 0x5c, 0xb8, 0x2d, 0x00,
+        0x00, 0x00, 0x0f, 0x49, 0x44, 0x41, 0x54, 0x20, 0x9a, 0xc9, 0x4f, 0x01,
+        0x04, 0xfd, 0x15, 0x10, 0x34, 0x00, 0xc1, 0xe7, 0x09, 0xf4, 0x33, 0xa4,
+        0xeb, 0xb1, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42,
+        0x60, 0x82,
+    ];
+    // 5×2 con paleta de 4 bits (filtros Sub y Average)
+    const PNG_PAL4: [u8; 109] = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d,
+        0x49, 0x48, 0x44, 0x52, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x02,
+        0x04, 0x03, 0x00, 0x00, 0x00, 0x62, 0x44, 0x0b, 0x6e, 0x00, 0x00, 0x00,
+        0x0c, 0x50, 0x4c, 0x54, 0x45, 0x0a, 0x14, 0x1e, 0xc8, 0x64, 0x32, 0x00,
+        0xff, 0x00, 0xff, 0xff, 0xff, 0x5a, 0x0e, 0x83, 0x11, 0x00, 0x00, 0x00,
+        0x08, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x64, 0x54, 0xba, 0xcb,
+        0xac, 0x07, 0x54, 0x25, 0xa5, 0x00, 0x00, 0x00, 0x08, 0x49, 0x44, 0x41,
+        0x54, 0x7c, 0x4f, 0x01, 0x00, 0x07, 0x85, 0x02, 0x26, 0xf6, 0xba, 0x09,
+        0xc0, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60,
+        0x82,
+    ];
+
+    /// Archivo temporal que se borra solo
+    struct Temp(std::path::PathBuf);
+
+    impl Temp {
+        fn new(name: &str) -> Self {
+            Self(std::env::temp_dir().join(format!("solar_system_{}_{}", std::process::id(), name)))
+        }
+    }
+
+    impl Drop for Temp {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn test_pixels(w: usize, h: usize) -> Vec<u32> {
+        (0..w * h).map(|i| (i as u32).wrapping_mul(0x9E37_79B9) >> 8).collect()
+    }
+
+    #[test]
+    fn png_round_trip() {
+        // más de un bloque "stored" de 65535 bytes
+        let (w, h) = (200, 130);
+        let pixels = test_pixels(w, h);
+        let tmp = Temp::new("rt.png");
+        write_png(&tmp.0, w, h, &pixels).unwrap();
+        let img = read_image(&tmp.0).unwrap();
+        assert_eq!((img.w, img.h), (w, h));
+        assert_eq!(img.pixels, pixels);
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let (w, h) = (7, 3);
+        let pixels = test_pixels(w, h);
+        let tmp = Temp::new("rt.ppm");
+        write_ppm(&tmp.0, w, h, &pixels).unwrap();
+        let img = read_image(&tmp.0).unwrap();
+        assert_eq!((img.w, img.h), (w, h));
+        assert_eq!(img.pixels, pixels);
+    }
+
+    #[test]
+    fn ppm_variants() {
+        let p3 = read_ppm(b"P3\n# comentario\n2 1\n15\n15 0 0  0 15 0\n").unwrap();
+        assert_eq!(p3.pixels, [0xFF0000, 0x00FF00]);
+        let p5 = read_ppm(b"P5 2 1 255\n\x10\x80").unwrap();
+        assert_eq!(p5.pixels, [0x101010, 0x808080]);
+        let p6 = read_ppm(b"P6 1 1 65535\n\xff\xff\x80\x00\x00\x00").unwrap();
+        assert_eq!(p6.pixels, [0xFF7F00]);
+    }
+
+    #[test]
+    fn png_filters() {
+        let img = read_png(&PNG_RGB8).unwrap();
+        assert_eq!((img.w, img.h), (4, 5));
+        for y in 0..5u32 {
+            for x in 0..4u32 {
+                let r = (x * 50 + y * 7) & 255;
+                let g = (x * y * 13) & 255;
+                let b = 255 - x * 20 - y * 30;
+                assert_eq!(img.pixels[(y * 4 + x) as usize], (r << 16) | (g << 8) | b, "({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn png_16_bit() {
+        let img = read_png(&PNG_RGB16).unwrap();
+        assert_eq!((img.w, img.h), (3, 2));
+        for y in 0..2u32 {
+            for x in 0..3u32 {
+                // se queda con el byte alto de cada muestra
+                let c = |c: u32| ((x * 1000 + y * 20000 + c * 300) & 0xFFFF) >> 8;
+                assert_eq!(img.pixels[(y * 3 + x) as usize], (c(0) << 16) | (c(1) << 8) | c(2));
+            }
+        }
+    }
+
+    #[test]
+    fn png_palette() {
+        let img = read_png(&PNG_PAL4).unwrap();
+        let palette = [0x0A141E, 0xC86432, 0x00FF00, 0xFFFFFF];
+        assert_eq!((img.w, img.h), (5, 2));
+        for y in 0..2 {
+            for x in 0..5 {
+                assert_eq!(img.pixels[y * 5 + x], palette[(x + 2 * y) % 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn tga_bottom_up_and_rle() {
+        // 2×2 de 24 bits, origen abajo: la primera fila del archivo es la de abajo
+        let mut raw = vec![0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0, 24, 0];
+        raw.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+        let img = read_tga(&raw).unwrap();
+        assert_eq!(img.pixels, [0x090807, 0x0C0B0A, 0x030201, 0x060504]);
+
+        // RLE con arriba primero: un paquete repetido de 3 y uno crudo de 1
+        let mut rle = vec![0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0, 24, 0x20];
+        rle.extend_from_slice(&[0x82, 1, 2, 3, 0x00, 4, 5, 6]);
+        let img = read_tga(&rle).unwrap();
+        assert_eq!(img.pixels, [0x030201, 0x030201, 0x030201, 0x060504]);
+    }
+
+    #[test]
+    fn malformed_images_fail() {
+        assert!(read_ppm(b"").is_err());
+        assert!(read_ppm(b"P").is_err());
+        assert!(read_ppm(b"P6").is_err());
+        assert!(read_ppm(b"P7 1 1 255\n\0\0\0").is_err());
+        assert!(read_ppm(b"P6 2 2 255\n\0\0\0").is_err());
+        assert!(read_ppm(b"P6 99999999999 99999999999 255\n").is_err());
+        assert!(read_ppm(b"P3 1 1 255\n1 2").is_err());
+
+        assert!(read_tga(&[0; 10]).is_err());
+        // 65535×65535 sin datos
+        assert!(read_tga(&[0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 24, 0]).is_err());
+        assert!(read_tga(&[0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 8, 0, 0]).is_err());
+
+        // PNG cortado en cualquier punto
+        for n in 0..PNG_RGB8.len() - 12 {
+            assert!(read_png(&PNG_RGB8[..n]).is_err(), "cortado en {}", n);
+        }
+        // IHDR con 0xFFFFFFFF × 0xFFFFFFFF y 16 bits RGBA
+        let mut huge = PNG_RGB8.to_vec();
+        huge[16..24].fill(0xFF);
+        huge[24] = 16;
+        huge[25] = 6;
+        assert!(read_png(&huge).is_err());
+        // filtro inexistente en la primera fila
+        let mut raw = vec![9u8];
+        raw.extend_from_slice(&[0; 3]);
+        let mut z = vec![0x78, 0x01, 1, 4, 0, !4, 0xFF];
+        z.extend_from_slice(&raw);
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut png, b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        png_chunk(&mut png, b"IDAT", &z);
+        png_chunk(&mut png, b"IEND", &[]);
+        assert!(read_png(&png).is_err());
+    }
+}
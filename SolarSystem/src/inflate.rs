@@ -0,0 +1,289 @@
+use std::io;
+
+// Descompresor DEFLATE (RFC 1951) dentro de un flujo zlib (RFC 1950), lo
+// justo para leer PNG sin dependencias.
+
+fn bad(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("deflate: {}", msg))
+}
+
+/// Lector de bits, del menos significativo al más significativo de cada byte
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buf: u64,
+    cnt: u32,
+}
+
+impl<'a> Bits<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0, buf: 0, cnt: 0 }
+    }
+
+    fn fill(&mut self) {
+        while self.cnt <= 56 && self.pos < self.data.len() {
+            self.buf |= (self.data[self.pos] as u64) << self.cnt;
+            self.pos += 1;
+            self.cnt += 8;
+        }
+    }
+
+    /// Los próximos `n` bits sin consumirlos (ceros pasado el final)
+    fn peek(&mut self, n: u32) -> u32 {
+        if self.cnt < n { self.fill(); }
+        (self.buf & ((1u64 << n) - 1)) as u32
+    }
+
+    fn consume(&mut self, n: u32) -> io::Result<()> {
+        if self.cnt < n { return Err(bad("datos cortados")); }
+        self.buf >>= n;
+        self.cnt -= n;
+        Ok(())
+    }
+
+    fn read(&mut self, n: u32) -> io::Result<u32> {
+        let v = self.peek(n);
+        self.consume(n)?;
+        Ok(v)
+    }
+
+    /// Descarta lo que queda del byte actual
+    fn align(&mut self) {
+        let drop = self.cnt % 8;
+        self.buf >>= drop;
+        self.cnt -= drop;
+    }
+}
+
+/// Código de Huffman canónico en una tabla indexada por los próximos bits
+struct Huffman {
+    /// (símbolo, largo); largo 0 = código inválido
+    table: Vec<(u16, u8)>,
+    bits: u32,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> io::Result<Self> {
+        let bits = lengths.iter().copied().max().unwrap_or(0) as u32;
+        let mut count = [0u32; 16];
+        for &l in lengths { count[l as usize] += 1; }
+        count[0] = 0;
+        let mut next = [0u32; 16];
+        let mut code = 0;
+        for l in 1..16 {
+            code = (code + count[l - 1]) << 1;
+            next[l] = code;
+        }
+
+        let mut table = vec![(0u16, 0u8); 1 << bits];
+        for (sym, &l) in lengths.iter().enumerate() {
+            if l == 0 { continue; }
+            let c = next[l as usize];
+            next[l as usize] += 1;
+            if c >= 1 << l { return Err(bad("tabla de Huffman inválida")); }
+            // los códigos van con el bit más significativo primero
+            let rev = c.reverse_bits() >> (32 - l as u32);
+            let mut i = rev as usize;
+            while i < table.len() {
+                table[i] = (sym as u16, l);
+                i += 1 << l;
+            }
+        }
+        Ok(Self { table, bits })
+    }
+
+    fn decode(&self, b: &mut Bits) -> io::Result<u16> {
+        let (sym, len) = self.table[b.peek(self.bits) as usize];
+        if len == 0 { return Err(bad("código inválido")); }
+        b.consume(len as u32)?;
+        Ok(sym)
+    }
+}
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+    35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+    3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+    257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6,
+    7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+// orden en que vienen los largos del código de los largos
+const CL_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn fixed_codes() -> io::Result<(Huffman, Huffman)> {
+    let mut lit = [8u8; 288];
+    lit[144..256].fill(9);
+    lit[256..280].fill(7);
+    Ok((Huffman::new(&lit)?, Huffman::new(&[5u8; 30])?))
+}
+
+fn dynamic_codes(b: &mut Bits) -> io::Result<(Huffman, Huffman)> {
+    let hlit = b.read(5)? as usize + 257;
+    let hdist = b.read(5)? as usize + 1;
+    let hclen = b.read(4)? as usize + 4;
+    let mut cl = [0u8; 19];
+    for &k in &CL_ORDER[..hclen] {
+        cl[k] = b.read(3)? as u8;
+    }
+    let cl = Huffman::new(&cl)?;
+
+    let mut lengths = vec![0u8; hlit + hdist];
+    let mut i = 0;
+    while i < lengths.len() {
+        let sym = cl.decode(b)?;
+        let (val, rep) = match sym {
+            0..=15 => (sym as u8, 1),
+            16 => {
+                if i == 0 { return Err(bad("repetición sin largo previo")); }
+                (lengths[i - 1], 3 + b.read(2)? as usize)
+            }
+            17 => (0, 3 + b.read(3)? as usize),
+            _ => (0, 11 + b.read(7)? as usize),
+        };
+        if i + rep > lengths.len() { return Err(bad("demasiados largos")); }
+        lengths[i..i + rep].fill(val);
+        i += rep;
+    }
+    if lengths[256] == 0 { return Err(bad("falta el código de fin de bloque")); }
+    Ok((Huffman::new(&lengths[..hlit])?, Huffman::new(&lengths[hlit..])?))
+}
+
+fn inflate_block(b: &mut Bits, out: &mut Vec<u8>, lit: &Huffman, dist: &Huffman) -> io::Result<()> {
+    loop {
+        let sym = lit.decode(b)? as usize;
+        if sym < 256 {
+            out.push(sym as u8);
+            continue;
+        }
+        if sym == 256 { return Ok(()); }
+        let k = sym - 257;
+        if k >= LEN_BASE.len() { return Err(bad("largo inválido")); }
+        let len = LEN_BASE[k] as usize + b.read(LEN_EXTRA[k] as u32)? as usize;
+        let d = dist.decode(b)? as usize;
+        if d >= DIST_BASE.len() { return Err(bad("distancia inválida")); }
+        let back = DIST_BASE[d] as usize + b.read(DIST_EXTRA[d] as u32)? as usize;
+        if back > out.len() { return Err(bad("distancia antes del comienzo")); }
+        // la copia puede pisarse a sí misma (back < len): byte a byte
+        let start = out.len() - back;
+        for j in 0..len {
+            out.push(out[start + j]);
+        }
+    }
+}
+
+/// Descomprime un flujo DEFLATE crudo
+pub fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut b = Bits::new(data);
+    let mut out = Vec::with_capacity(data.len() * 4);
+    loop {
+        let last = b.read(1)? == 1;
+        match b.read(2)? {
+            0 => {
+                b.align();
+                let len = b.read(16)?;
+                let nlen = b.read(16)?;
+                if len != !nlen & 0xFFFF { return Err(bad("bloque sin comprimir corrupto")); }
+                for _ in 0..len {
+                    out.push(b.read(8)? as u8);
+                }
+            }
+            1 => {
+                let (lit, dist) = fixed_codes()?;
+                inflate_block(&mut b, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_codes(&mut b)?;
+                inflate_block(&mut b, &mut out, &lit, &dist)?;
+            }
+            _ => return Err(bad("tipo de bloque inválido")),
+        }
+        if last { return Ok(out); }
+    }
+}
+
+/// Descomprime un flujo zlib (cabecera de 2 bytes + DEFLATE + Adler-32); el
+/// checksum del final no se verifica
+pub fn zlib_decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    if data.len() < 2 { return Err(bad("zlib: flujo vacío")); }
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0F != 8 || !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) {
+        return Err(bad("zlib: cabecera inválida"));
+    }
+    if flg & 0x20 != 0 { return Err(bad("zlib: diccionario no soportado")); }
+    inflate(&data[2..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORED: [u8; 22] = [
+        0x78, 0x01, 0x01, 0x0b, 0x00, 0xf4, 0xff, 0x68, 0x6f, 0x6c, 0x61, 0x2c,
+        0x20, 0x6d, 0x75, 0x6e, 0x64, 0x6f, 0x18, 0x17, 0x04, 0x14,
+    ];
+    // bloque con los códigos fijos
+    const FIXED: [u8; 18] = [
+        0x78, 0xda, 0x4b, 0x4c, 0x4a, 0x4e, 0x44, 0x45, 0x0a, 0x19, 0xf9, 0x39,
+        0x89, 0x00, 0x68, 0xbb, 0x08, 0xa9,
+    ];
+    // bloque con códigos dinámicos
+    const DYNAMIC: [u8; 61] = [
+        0x78, 0xda, 0xed, 0x8d, 0xc1, 0x0d, 0x00, 0x20, 0x0c, 0x02, 0x67, 0xe5,
+        0x0c, 0xfb, 0xaf, 0xa0, 0x25, 0xda, 0x97, 0x23, 0x94, 0x47, 0x13, 0xe8,
+        0x11, 0x04, 0xb6, 0xd7, 0x93, 0x41, 0x12, 0x24, 0x4d, 0x7e, 0x6e, 0x59,
+        0x95, 0x68, 0x32, 0x5f, 0x42, 0xba, 0xfb, 0x21, 0x75, 0xfb, 0xb8, 0xc1,
+        0x58, 0xcd, 0xd0, 0x0c, 0xcd, 0xd0, 0x6f, 0x68, 0x03, 0xa5, 0xbd, 0x5c,
+        0x23,
+    ];
+
+    fn dynamic_input() -> Vec<u8> {
+        (0..300u32).flat_map(|i| std::iter::repeat_n(b'a' + (i * i % 7) as u8, (i % 5 + 1) as usize)).collect()
+    }
+
+    #[test]
+    fn stored_block() {
+        assert_eq!(zlib_decompress(&STORED).unwrap(), b"hola, mundo");
+    }
+
+    #[test]
+    fn fixed_huffman() {
+        assert_eq!(zlib_decompress(&FIXED).unwrap(), b"abcabcabcabcabcabc hola");
+    }
+
+    #[test]
+    fn dynamic_huffman() {
+        assert_eq!(zlib_decompress(&DYNAMIC).unwrap(), dynamic_input());
+    }
+
+    #[test]
+    fn malformed_streams_fail() {
+        assert!(zlib_decompress(&[]).is_err());
+        assert!(zlib_decompress(&[0x78]).is_err());
+        // cabecera que no es múltiplo de 31
+        assert!(zlib_decompress(&[0x78, 0x00, 0x01]).is_err());
+        // tipo de bloque 3
+        assert!(inflate(&[0x07]).is_err());
+        // distancia antes del comienzo: largo 3 hacia atrás sin nada escrito
+        let err = inflate(&[0x03, 0x02]).unwrap_err();
+        assert!(err.to_string().contains("distancia"), "{}", err);
+        // cortado a la mitad en cada punto posible
+        for n in 2..DYNAMIC.len() - 4 {
+            assert!(zlib_decompress(&DYNAMIC[..n]).is_err(), "cortado en {}", n);
+        }
+        // bytes al azar: error o lo que salga, pero sin pánico
+        let mut s = 0x1234_5678u32;
+        for _ in 0..200 {
+            let junk: Vec<u8> = (0..64).map(|_| { s ^= s << 13; s ^= s >> 17; s ^= s << 5; s as u8 }).collect();
+            let _ = inflate(&junk);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Renderer 3D por software (CPU) de sistemas solares.
+//!
+//! Uso mínimo: armar una [`Scene`], una [`Camera`] y un [`Renderer`], y
+//! llamar a [`Renderer::render_into`] con un buffer `&mut [u32]` (0xRRGGBB)
+//! propio cada frame:
+//!
+//! ```no_run
+//! use solar_system::{Camera, Renderer, Scene};
+//!
+//! let scene = Scene::default_system();
+//! let cam = Camera::new();
+//! let mut renderer = Renderer::new(320, 200);
+//! let mut pixels = vec![0u32; 320 * 200];
+//! renderer.render_into(&scene, &cam, 0.0, &mut pixels);
+//! ```
+
+pub mod math;
+pub mod draw;
+pub mod bands;
+pub mod window;
+pub mod headless;
+pub mod image_io;
+pub mod inflate;
+pub mod projector;
+pub mod camera;
+pub mod clock;
+pub mod skybox;
+pub mod hud;
+pub mod bodies;
+pub mod orbits;
+pub mod shading;
+pub mod material;
+pub mod atmosphere;
+pub mod ring_render;
+pub mod eclipse;
+pub mod bloom;
+pub mod star;
+pub mod texture;
+pub mod bake;
+pub mod ship;
+pub mod mesh_render;
+pub mod obj_loader;
+pub mod nbody;
+pub mod noise;
+pub mod scene;
+pub mod system_file;
+pub mod renderer;
+
+pub use crate::bodies::body::Body;
+pub use crate::camera::Camera;
+pub use crate::clock::SimClock;
+pub use crate::renderer::Renderer;
+pub use crate::scene::{Scene, SceneBody};
+pub use crate::shading::PlanetKind;
+pub use crate::material::PlanetMaterial;
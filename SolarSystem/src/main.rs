@@ -1,291 +1,231 @@
-mod math;
-mod draw;
-mod window;
-mod projector;
-mod camera;
-mod skybox;
-mod hud;
-mod bodies;
-mod orbits;
-mod shading;
-mod ship;
-mod obj_loader;
+mod cli;
 
+use std::io;
+use std::path::Path;
 use std::time::{Instant, Duration};
 
-use crate::window::{WindowCtx, Key};
-use crate::projector::Projector;
-use crate::camera::Camera;
-use crate::skybox::{make_stars, draw_stars};
-use crate::hud::reticle;
-use crate::bodies::body::Body;
-use crate::orbits::draw_orbit_3d;
-use crate::draw::{BG, rgb, draw_disc};
-use crate::shading::{PlanetKind, draw_shaded_sphere};
-use crate::ship::Ship;
+use solar_system::window::{Backend, WindowCtx, Key};
+use solar_system::headless::HeadlessCtx;
+use solar_system::{Camera, Renderer, Scene, SimClock};
+use solar_system::ship::Ship;
+use solar_system::system_file::load_system;
+use solar_system::bake::{DEFAULT_BAKE_WIDTH, bake_surface, export_maps as write_maps};
+use solar_system::bands::default_threads;
 
-fn resolve_collisions(cam: &mut Camera, sun: &Body, planets: &[Body], t: f32) {
-    // evita que la cámara entre al sol y planetas
-    let mut push_out = |center: crate::math::Vec3, radius: f32| {
-        let diff = cam.pos.sub(center);
-        let dist = diff.len();   
-        if dist <= 0.0001 {
-            return;
-        }
-        // factor para no quedar pegado tan cerca
-        let min_dist = radius * 1.3;
-        if dist < min_dist {
-            let dir = diff.mul(1.0 / dist);
-            cam.pos = center.add(dir.mul(min_dist));
+fn main() {
+    let opts = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(o) => o,
+        Err(msg) => {
+            if msg.is_empty() {
+                println!("{}", cli::USAGE);
+                return;
+            }
+            eprintln!("{}\n\n{}", msg, cli::USAGE);
+            std::process::exit(2);
         }
     };
 
-    // Sol (con radio algo más grande, porque es enorme visualmente)
-    push_out(sun.pos(t), sun.radius * 2.0);
+    // Sistema: el del archivo dado o el que trae el binario
+    let mut scene = match &opts.system {
+        Some(path) => load_system(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            std::process::exit(1);
+        }),
+        None => Scene::default_system(),
+    };
+    let bake_threads = opts.threads.unwrap_or_else(default_threads);
+    if let Some(dir) = &opts.export_maps {
+        export_maps(&scene, dir, opts.bake.unwrap_or(DEFAULT_BAKE_WIDTH), bake_threads);
+        return;
+    }
+    if let Some(width) = opts.bake {
+        let n = scene.bake_surfaces(width, bake_threads);
+        eprintln!("superficies horneadas: {} ({}x{})", n, width, width / 2);
+    }
+    if opts.nbody {
+        scene.start_nbody(0.0);
+    }
 
-    // Planetas
-    for p in planets {
-        push_out(p.pos(t), p.radius * 1.5);
+    match opts.headless {
+        Some(h) => {
+            let mut backend = HeadlessCtx::new(opts.width, opts.height, h.frames, h.dt, h.out, h.format)
+                .unwrap_or_else(|e| {
+                    eprintln!("no se pudo preparar la salida headless: {}", e);
+                    std::process::exit(1);
+                });
+            run(&mut backend, &mut scene, opts.time_scale, opts.threads);
+        }
+        None => {
+            // Ventana relativamente ligera
+            let mut win = WindowCtx::new(opts.width, opts.height, "Solar 3D — Sistema con Shaders");
+            run(&mut win, &mut scene, opts.time_scale, opts.threads);
+        }
     }
 }
 
+/// Hornea cada cuerpo que se pueda y escribe sus mapas en `dir`
+fn export_maps(scene: &Scene, dir: &Path, width: usize, threads: usize) {
+    for sb in &scene.bodies {
+        let name = &sb.body.name;
+        let Some(maps) = bake_surface(&sb.material, sb.body.seed, width, threads) else {
+            eprintln!("{}: no se hornea (superficie animada o ya pintada)", name);
+            continue;
+        };
+        match write_maps(&maps, dir, name) {
+            Ok(paths) => {
+                for p in paths {
+                    eprintln!("{}: {}", name, p.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("{}: no se pudieron escribir los mapas: {}", dir.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
 
-fn main() {
-    // Ventana relativamente ligera
-    let mut win = WindowCtx::new(800, 480, "Solar 3D — Sistema con Shaders");
-    let mut buf = vec![BG; win.width * win.height];
-
-    // Cámara y proyector
-    let mut cam = Camera::new();
-    let proj = Projector::new(win.width, win.height, 60.0, 0.1, 5000.0);
-
-    // Skybox
-    let stars = make_stars(1200, 0xC0FFEE);
+/// Loop principal, igual para ventana y headless
+fn run(win: &mut dyn Backend, scene: &mut Scene, time_scale: f32, threads: Option<usize>) {
+    let (width, height) = win.size();
 
-    let start = Instant::now();
+    // Renderer con la nave que sigue a la cámara y la retícula
+    let mut renderer = Renderer::new(width, height);
+    renderer.ship = Some(Ship::new());
+    renderer.show_hud = true;
+    if let Some(n) = threads {
+        renderer.threads = n;
+    }
 
-    // ----------------- Sistema: Sol + 4 planetas -----------------
+    let mut cam = Camera::new();
 
-    let sun = Body {
-        name: "Sol",
-        radius: 28.0,
-        orbit_r: 0.0,
-        orbit_speed: 0.0,
-        rot_speed: 0.2,
-        phase: 0.0,
-        color: rgb(255, 210, 60),
-        draw_orbit: false,
+    // headless: paso fijo; ventana: dt real medido cada frame
+    let mut clock = match win.fixed_dt() {
+        Some(step) => SimClock::fixed(step),
+        None => SimClock::new(),
     };
+    clock.set_time_scale(time_scale);
 
-    let planets = vec![
-        Body {
-            name: "Aurea",   // rocoso
-            radius: 9.0,
-            orbit_r: 130.0,
-            orbit_speed: 0.7,
-            rot_speed: 1.3,
-            phase: 0.0,
-            color: rgb(255, 180, 120),
-            draw_orbit: true,
-        },
-        Body {
-            name: "Cobalt",  // gigante gaseoso
-            radius: 14.0,
-            orbit_r: 220.0,
-            orbit_speed: 0.45,
-            rot_speed: 1.0,
-            phase: 1.1,
-            color: rgb(100, 160, 255),
-            draw_orbit: true,
-        },
-        Body {
-            name: "Verdia",  // helado
-            radius: 12.0,
-            orbit_r: 310.0,
-            orbit_speed: 0.28,
-            rot_speed: 0.9,
-            phase: 2.1,
-            color: rgb(120, 255, 255),
-            draw_orbit: true,
-        },
-        Body {
-            name: "Crimson", // volcánico
-            radius: 13.0,
-            orbit_r: 410.0,
-            orbit_speed: 0.19,
-            rot_speed: 0.5,
-            phase: -1.3,
-            color: rgb(255, 90, 90),
-            draw_orbit: true,
-        },
-    ];
-
-    // Tipo de shader para cada cuerpo
-    let sun_kind = PlanetKind::Star;
-    let planet_kinds = vec![
-        PlanetKind::Rocky,     // Aurea
-        PlanetKind::GasGiant,  // Cobalt
-        PlanetKind::Ice,       // Verdia
-        PlanetKind::Volcanic,  // Crimson
-    ];
-
-    // Nave que sigue a la cámara
-    let ship = Ship::new();
-
-    // modo bonito vs modo rápido (P)
-    let mut pretty_mode = true;
-
-    #[derive(Clone)]
-    struct DrawItem {
-        z: f32,
-        sx: i32,
-        sy: i32,
-        r_px: i32,
-        kind: PlanetKind,
-    }
+    // diagnóstico de energía del modo N-cuerpos cada tantos segundos simulados
+    let mut next_report = 0.0;
+    // si ya se avisó que el N-cuerpos no llega a la escala de tiempo
+    let mut warned_drop = false;
 
     // ------------------------- Loop principal ---------------------------
     while win.is_open() {
-        let dt = 0.016;
-        let t = start.elapsed().as_secs_f32();
+        let frame_start = Instant::now();
+
+        // Controles del reloj: K pausa, . un paso, R reversa, +/- escala, 0 x1
+        let mut clock_changed = true;
+        if win.key_pressed(Key::K) {
+            clock.toggle_pause();
+        } else if win.key_pressed(Key::Period) {
+            clock.step_once();
+        } else if win.key_pressed(Key::R) {
+            clock.toggle_reverse();
+        } else if win.key_pressed(Key::Equal) {
+            clock.faster();
+        } else if win.key_pressed(Key::Minus) {
+            clock.slower();
+        } else if win.key_pressed(Key::Key0) {
+            clock.reset_scale();
+        } else {
+            clock_changed = false;
+        }
+        if clock_changed {
+            eprintln!("reloj: {}", clock.describe());
+        }
+
+        clock.tick();
+        // cámara con el dt real; cuerpos y shaders con el tiempo simulado
+        let dt = clock.real_dt;
+        let t = clock.sim_time;
 
         // Targets para warp 1..5 (Sol + planetas)
-        let mut warp_targets = Vec::new();
-        warp_targets.push(sun.pos(t));
-        for p in &planets {
-            warp_targets.push(p.pos(t));
-        }
+        let warp_targets = scene.warp_targets(t);
 
         // Movimiento normal (W,S adelante/atrás; A,D strafe; Space/Ctrl subir/bajar)
-        cam.handle_input(&win, dt);
+        cam.handle_input(win, dt);
 
         // Toggles
         if win.key_pressed(Key::T) {
             cam.warp_anim_enabled = !cam.warp_anim_enabled;
         }
         if win.key_pressed(Key::P) {
-            pretty_mode = !pretty_mode;
+            renderer.pretty_mode = !renderer.pretty_mode;
         }
-
-        // Warp a cuerpos con 1–5
-        cam.handle_warp_keys(&win, &warp_targets);
-
-        // Animación de warp
-        cam.update_warp(dt);
-
-        // Colisiones cámara / cuerpos
-        resolve_collisions(&mut cam, &sun, &planets, t);
-
-        // Limpiar buffer
-        buf.fill(BG);
-
-        // Skybox solo en modo bonito (para ahorrar CPU en modo rápido)
-        if pretty_mode {
-            draw_stars(&mut buf, &cam, &proj, &stars, win.width, win.height);
+        if win.key_pressed(Key::B) {
+            renderer.bloom = !renderer.bloom;
+            eprintln!("bloom: {}", if renderer.bloom { "encendido" } else { "apagado" });
+        }
+        if win.key_pressed(Key::M)
+            && let Some(ship) = &mut renderer.ship
+        {
+            ship.wireframe = !ship.wireframe;
         }
 
-        // Órbitas 
-        for p in &planets {
-            if p.draw_orbit {
-                draw_orbit_3d(
-                    &mut buf,
-                    &cam,
-                    &proj,
-                    p.orbit_r,
-                    win.width,
-                    win.height,
-                    rgb(40, 40, 70),
-                );
+        // Movimiento analítico ↔ simulación N-cuerpos
+        if win.key_pressed(Key::N) {
+            if scene.is_simulated() {
+                scene.stop_nbody();
+                eprintln!("n-body: apagado (órbitas analíticas)");
+            } else {
+                scene.start_nbody(t);
+                next_report = 0.0;
+                warned_drop = false;
+                eprintln!("n-body: encendido (G = {})", scene.nbody.g);
             }
         }
-
-        // Proyección de Sol + planetas
-        let mut items: Vec<DrawItem> = Vec::new();
-
-        // Sol
+        scene.step(clock.sim_dt);
+        if let Some(sim) = &scene.sim
+            && sim.dropped > 0.0
+            && !warned_drop
         {
-            let ws = sun.pos(t);
-            let cp = Projector::world_to_camera(ws, &cam);
-            if cp.z > proj.z_near && cp.z < proj.z_far {
-                if let Some((sx, sy)) = proj.project(cp) {
-                    let rpx = proj.radius_world_to_px(sun.radius, cp.z);
-                    items.push(DrawItem {
-                        z: cp.z,
-                        sx,
-                        sy,
-                        r_px: rpx,
-                        kind: sun_kind,
-                    });
-                }
-            }
+            eprintln!("n-body: no da abasto a esta escala de tiempo, va atrasado respecto del reloj");
+            warned_drop = true;
         }
-
-        // Planetas
-        for (i, p) in planets.iter().enumerate() {
-            let ws = p.pos(t);
-            let cp = Projector::world_to_camera(ws, &cam);
-            if cp.z > proj.z_near && cp.z < proj.z_far {
-                if let Some((sx, sy)) = proj.project(cp) {
-                    let rpx = proj.radius_world_to_px(p.radius, cp.z);
-                    items.push(DrawItem {
-                        z: cp.z,
-                        sx,
-                        sy,
-                        r_px: rpx,
-                        kind: planet_kinds[i],
-                    });
-                }
-            }
+        // a stderr: en headless stdout puede ser el video
+        if let Some(sim) = &scene.sim
+            && sim.time >= next_report
+        {
+            eprintln!(
+                "n-body: t = {:.1} s, E = {:.6e}, deriva = {:+.3e}, descartado = {:.1} s",
+                sim.time,
+                sim.energy(),
+                sim.energy_drift(),
+                sim.dropped
+            );
+            next_report = sim.time + 5.0;
         }
 
-        // Painter’s: de lejos a cerca
-        items.sort_by(|a, b| a.z.partial_cmp(&b.z).unwrap());
+        // Warp a cuerpos con 1–5
+        cam.handle_warp_keys(win, &warp_targets);
+
+        // Animación de warp
+        cam.update_warp(dt);
 
-        // Dibujar según modo
-        for it in items {
-            if pretty_mode {
-                // modo bonito: shader por píxel
-                draw_shaded_sphere(
-                    &mut buf,
-                    win.width,
-                    win.height,
-                    it.sx,
-                    it.sy,
-                    it.r_px,
-                    it.kind,
-                    t,
-                );
-            } else {
-                // modo rápido: solo discos de color plano
-                let base_color = match it.kind {
-                    PlanetKind::Star     => rgb(255, 230, 150),
-                    PlanetKind::Rocky    => rgb(170, 140, 110),
-                    PlanetKind::GasGiant => rgb(190, 170, 200),
-                    PlanetKind::Ice      => rgb(180, 220, 255),
-                    PlanetKind::Volcanic => rgb(200, 80, 40),
-                };
-                draw_disc(
-                    &mut buf,
-                    win.width,
-                    win.height,
-                    it.sx,
-                    it.sy,
-                    it.r_px,
-                    base_color,
-                );
+        // Colisiones cámara / cuerpos
+        scene.resolve_collisions(&mut cam, t);
+
+        // Dibujar y presentar frame
+        let frame_buf = renderer.render(scene, &cam, t);
+        match win.present(frame_buf) {
+            Ok(()) => {}
+            // el que leía el video (p. ej. `| head`) ya no quiere más
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return,
+            Err(e) => {
+                eprintln!("no se pudo presentar el frame: {}", e);
+                std::process::exit(1);
             }
         }
 
-        // Nave siguiendo a la cámara
-        ship.draw(&mut buf, win.width, win.height, &cam, &proj);
-
-        // HUD
-        reticle(&mut buf, win.width, win.height);
-
-        // Presentar frame
-        win.present(&buf);
-
-        std::thread::sleep(Duration::from_millis(16));
+        // ~60 fps en ventana; el dt medido absorbe lo que falte o sobre
+        if win.fixed_dt().is_none() {
+            let budget = Duration::from_millis(16);
+            let spent = frame_start.elapsed();
+            if spent < budget {
+                std::thread::sleep(budget - spent);
+            }
+        }
     }
 }
-
@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use crate::atmosphere::Atmosphere;
+use crate::draw::rgb;
+use crate::math::Vec3;
+use crate::shading::PlanetKind;
+use crate::texture::SurfaceMaps;
+
+/// Parámetros de la superficie de un cuerpo. `kind` elige el modelo de
+/// sombreado (cómo se combinan los ruidos) y el resto son los números que
+/// ese modelo usa, así un planeta nuevo es solo otro juego de valores.
+///
+/// Los colores van en RGB lineal 0..1 (pueden pasarse de 1 para brillar).
+#[derive(Clone, Debug)]
+pub struct PlanetMaterial {
+    pub kind: PlanetKind,
+    /// Color de las zonas bajas / bandas oscuras / roca
+    pub low: Vec3,
+    /// Color de las zonas altas / bandas claras / hielo limpio
+    pub high: Vec3,
+    /// Detalle: lava, grietas o remolinos según el modelo
+    pub accent: Vec3,
+    /// Color de los casquetes polares
+    pub cap_color: Vec3,
+    /// Frecuencia de los rasgos grandes (continentes, llanuras, flujo)
+    pub noise_scale: f32,
+    /// Frecuencia de los detalles (cordilleras, grietas, remolinos)
+    pub detail_scale: f32,
+    /// Bandas de latitud (gigantes gaseosos)
+    pub bands: f32,
+    /// |latitud| local (0..1) desde la que empieza el casquete; 1 = sin casquete
+    pub ice_cap_lat: f32,
+    /// Fracción de la superficie cubierta de lava (0..1)
+    pub lava_coverage: f32,
+    /// Cuánto brilla la lava sin luz del Sol (0 = solo reflejada); en una
+    /// estrella escala todo su brillo
+    pub emission: f32,
+    /// Densidad de luces de ciudades en el lado nocturno (0 = ninguna)
+    pub city_lights: f32,
+    pub city_color: Vec3,
+    /// Intensidad de las auroras polares del lado nocturno (0 = ninguna)
+    pub aurora: f32,
+    pub aurora_color: Vec3,
+    /// Luz ambiente del lado nocturno
+    pub ambient: f32,
+    /// Intensidad del reflejo del Sol en lo liso (mar, hielo); 0 = todo mate
+    pub specular: f32,
+    /// Rugosidad de lo liso (0..1): bajo = reflejo chico y fuerte
+    pub roughness: f32,
+    /// Cuánto de las zonas bajas es mar (0..1, modelo rocoso)
+    pub ocean: f32,
+    /// Cuántas manchas tiene una estrella (0..1)
+    pub sunspots: f32,
+    /// Mapas pintados del modelo `Textured`, u horneados de uno estático
+    /// (`bake::bake_surface`); compartidos entre copias
+    pub maps: Option<Arc<SurfaceMaps>>,
+    /// Capa de aire opcional (halo en el limbo, tinte en el terminador)
+    pub atmosphere: Option<Atmosphere>,
+    /// Capa de nubes opcional sobre la superficie
+    pub clouds: Option<Clouds>,
+}
+
+/// Nubes: otra capa de ruido que gira a su propia velocidad sobre la
+/// superficie, se mezcla encima y le proyecta una sombra leve.
+#[derive(Clone, Copy, Debug)]
+pub struct Clouds {
+    /// Fracción del cielo cubierta (0..1)
+    pub coverage: f32,
+    /// Velocidad angular extra respecto de la superficie (rad/s)
+    pub drift: f32,
+    /// Frecuencia del ruido de las nubes
+    pub scale: f32,
+    pub color: Vec3,
+    /// Opacidad de una nube densa (0..1)
+    pub opacity: f32,
+    /// Cuánta luz le quita la nube al suelo de abajo (0..1)
+    pub shadow: f32,
+}
+
+impl Default for Clouds {
+    fn default() -> Self {
+        Self {
+            coverage: 0.45,
+            drift: 0.05,
+            scale: 2.5,
+            color: Vec3::new(0.95, 0.96, 1.0),
+            opacity: 0.9,
+            shadow: 0.5,
+        }
+    }
+}
+
+impl PlanetMaterial {
+    /// Los valores de siempre para cada modelo
+    pub fn preset(kind: PlanetKind) -> Self {
+        let base = Self {
+            kind,
+            low: Vec3::new(0.2, 0.2, 0.2),
+            high: Vec3::new(0.6, 0.6, 0.6),
+            accent: Vec3::new(1.0, 1.0, 1.0),
+            cap_color: Vec3::new(0.9, 0.92, 0.95),
+            noise_scale: 2.0,
+            detail_scale: 4.0,
+            bands: 0.0,
+            ice_cap_lat: 1.0,
+            lava_coverage: 0.0,
+            emission: 0.0,
+            city_lights: 0.0,
+            city_color: Vec3::new(1.0, 0.8, 0.45),
+            aurora: 0.0,
+            aurora_color: Vec3::new(0.2, 1.0, 0.5),
+            ambient: 0.06,
+            specular: 0.0,
+            roughness: 0.3,
+            ocean: 0.0,
+            sunspots: 0.0,
+            maps: None,
+            atmosphere: None,
+            clouds: None,
+        };
+        match kind {
+            // el color lo pone la temperatura (`Body::temperature`); la
+            // paleta es solo cuánto brilla y `accent` son las protuberancias
+            PlanetKind::Star => Self {
+                low: Vec3::new(0.85, 0.85, 0.85),
+                high: Vec3::new(1.05, 1.05, 1.05),
+                accent: Vec3::new(1.0, 0.35, 0.25),
+                noise_scale: 2.5,
+                detail_scale: 40.0,
+                sunspots: 0.5,
+                emission: 1.0,
+                ambient: 1.0,
+                ..base
+            },
+            PlanetKind::Rocky => Self {
+                low: Vec3::new(0.22, 0.15, 0.12),
+                high: Vec3::new(0.4, 0.3, 0.22),
+                accent: Vec3::new(0.6, 0.45, 0.33),
+                noise_scale: 1.6,
+                detail_scale: 4.0,
+                ice_cap_lat: 0.72,
+                specular: 3.0,
+                roughness: 0.3,
+                ..base
+            },
+            PlanetKind::GasGiant => Self {
+                low: Vec3::new(0.7, 0.55, 0.4),
+                high: Vec3::new(0.9, 0.85, 0.8),
+                accent: Vec3::new(1.0, 0.95, 0.9),
+                noise_scale: 2.0,
+                detail_scale: 3.0,
+                bands: 10.0,
+                ambient: 0.05,
+                ..base
+            },
+            PlanetKind::Ice => Self {
+                low: Vec3::new(0.3, 0.5, 0.8),
+                high: Vec3::new(0.75, 0.85, 0.95),
+                accent: Vec3::new(0.21, 0.35, 0.56),
+                noise_scale: 2.5,
+                detail_scale: 5.0,
+                specular: 2.0,
+                roughness: 0.4,
+                ..base
+            },
+            PlanetKind::Volcanic => Self {
+                low: Vec3::new(0.096, 0.064, 0.064),
+                high: Vec3::new(0.144, 0.096, 0.096),
+                accent: Vec3::new(1.2, 0.5, 0.1),
+                noise_scale: 4.0,
+                detail_scale: 3.0,
+                lava_coverage: 0.3,
+                emission: 0.6,
+                ambient: 0.05,
+                ..base
+            },
+            // los colores salen de los mapas; `emission` escala el nocturno y
+            // `specular` el brillo que marca el mapa especular
+            PlanetKind::Textured => Self {
+                emission: 1.0,
+                specular: 1.0,
+                roughness: 0.3,
+                ..base
+            },
+        }
+    }
+
+    /// Color plano para el modo rápido: el albedo promedio de lo que pinta el
+    /// shader más lo que emite (la estrella es pura emisión)
+    pub fn flat_color(&self) -> u32 {
+        let mix = |a: Vec3, b: Vec3, t: f32| a.mul(1.0 - t).add(b.mul(t));
+        let mut c = mix(self.low, self.high, 0.5);
+        if let (Some(maps), PlanetKind::Textured) = (&self.maps, self.kind) {
+            c = maps.albedo.average();
+        } else if self.kind == PlanetKind::Star {
+            c = c.mul(self.emission);
+        } else {
+            c = mix(c, self.accent, self.lava_coverage * 0.6);
+            c = mix(c, self.cap_color, (1.0 - self.ice_cap_lat).clamp(0.0, 1.0) * 0.5);
+            c = c.add(self.accent.mul(self.lava_coverage * self.emission * 0.3));
+        }
+        let ch = |v: f32| (v.clamp(0.0, 1.0) * 255.0) as u8;
+        rgb(ch(c.x), ch(c.y), ch(c.z))
+    }
+
+    /// Copia con la paleta teñida por `color` (el de cuerpo negro de una estrella)
+    pub fn tinted(&self, color: Vec3) -> Self {
+        let tint = |c: Vec3| Vec3::new(c.x * color.x, c.y * color.y, c.z * color.z);
+        Self { low: tint(self.low), high: tint(self.high), ..self.clone() }
+    }
+}
+
+impl From<PlanetKind> for PlanetMaterial {
+    fn from(kind: PlanetKind) -> Self {
+        Self::preset(kind)
+    }
+}
@@ -3,6 +3,8 @@ use std::f32::consts::PI;
 #[derive(Clone, Copy, Debug)]
 pub struct Vec3 { pub x: f32, pub y: f32, pub z: f32 }
 
+// add/sub/mul con nombre propio en vez de los traits de std::ops
+#[allow(clippy::should_implement_trait)]
 impl Vec3 {
     pub fn new(x: f32, y: f32, z: f32) -> Self { Self { x, y, z } }
     pub fn add(self, o: Vec3) -> Vec3 { Vec3::new(self.x+o.x, self.y+o.y, self.z+o.z) }
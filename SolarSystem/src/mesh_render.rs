@@ -0,0 +1,174 @@
+use crate::math::Vec3;
+use crate::projector::Projector;
+use crate::draw::{Band, Prim, scale_rgb};
+use crate::obj_loader::Mesh;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MeshStyle {
+    /// Triángulos rellenos con sombreado plano por cara
+    Solid,
+    /// Aristas de 1 de cada `face_stride` caras (modo barato)
+    Wireframe { face_stride: usize },
+}
+
+/// Dibuja una malla. `to_cam` lleva cada vértice del modelo a espacio de
+/// cámara y `light_cam` es la posición de la luz (el Sol) en ese espacio.
+pub fn draw_mesh(
+    fb: &mut Band,
+    proj: &Projector,
+    mesh: &Mesh,
+    to_cam: impl Fn(Vec3) -> Vec3,
+    light_cam: Vec3,
+    color: u32,
+    style: MeshStyle,
+) {
+    for p in mesh_prims(proj, mesh, to_cam, light_cam, color, style) {
+        p.draw(fb);
+    }
+}
+
+/// Como `draw_mesh`, pero devuelve los triángulos o líneas ya proyectados y
+/// sombreados, en orden, para rasterizarlos después (por franjas)
+pub fn mesh_prims(
+    proj: &Projector,
+    mesh: &Mesh,
+    to_cam: impl Fn(Vec3) -> Vec3,
+    light_cam: Vec3,
+    color: u32,
+    style: MeshStyle,
+) -> Vec<Prim> {
+    let mut prims = Vec::new();
+    let n_verts = mesh.verts.len();
+    if n_verts == 0 {
+        return prims;
+    }
+
+    let mut cam_verts = vec![Vec3::new(0.0, 0.0, 0.0); n_verts];
+    let mut screen_verts = vec![None; n_verts];
+
+    for (i, v) in mesh.verts.iter().enumerate() {
+        let cp = to_cam(*v);
+        cam_verts[i] = cp;
+
+        if cp.z <= proj.z_near || cp.z >= proj.z_far {
+            continue;
+        }
+
+        if let Some((sx, sy)) = proj.project_f(cp) {
+            screen_verts[i] = Some(Vec3::new(sx, sy, cp.z));
+        }
+    }
+
+    for (face_idx, face) in mesh.faces.iter().enumerate() {
+        if let MeshStyle::Wireframe { face_stride } = style
+            && face_idx % face_stride.max(1) != 0
+        {
+            continue;
+        }
+
+        let [i0, i1, i2] = *face;
+        if i0 >= n_verts || i1 >= n_verts || i2 >= n_verts {
+            continue;
+        }
+
+        let v0 = cam_verts[i0];
+        let v1 = cam_verts[i1];
+        let v2 = cam_verts[i2];
+
+        // back-face culling en perspectiva: la cara tiene que mirar a la
+        // cámara (el origen), no solo hacia -z
+        let n = v1.sub(v0).cross(v2.sub(v0)).norm();
+        if n.dot(v0) >= 0.0 {
+            continue;
+        }
+
+        // proyectado sin recortar si los tres vértices entran
+        let screen = match (screen_verts[i0], screen_verts[i1], screen_verts[i2]) {
+            (Some(s0), Some(s1), Some(s2)) => Some([s0, s1, s2]),
+            _ => None,
+        };
+
+        match style {
+            MeshStyle::Solid => {
+                // lambert por cara hacia la luz + un poco de ambiente
+                let centroid = v0.add(v1).add(v2).mul(1.0 / 3.0);
+                let l = light_cam.sub(centroid).norm();
+                let lambert = 0.25 + 0.75 * n.dot(l).max(0.0);
+                let color = scale_rgb(color, lambert);
+                if let Some(v) = screen {
+                    prims.push(Prim::Triangle { v, color });
+                    continue;
+                }
+                // cruza el plano cercano (o el lejano): se recorta y queda un
+                // polígono convexo que se parte en abanico
+                let poly = clip_polygon(proj, &[v0, v1, v2]);
+                let pts: Vec<Vec3> = poly.iter().filter_map(|&c| screen_point(proj, c)).collect();
+                if pts.len() != poly.len() {
+                    continue;
+                }
+                for k in 2..pts.len() {
+                    prims.push(Prim::Triangle { v: [pts[0], pts[k - 1], pts[k]], color });
+                }
+            }
+            MeshStyle::Wireframe { .. } => {
+                for (a, b) in [(v0, v1), (v1, v2), (v2, v0)] {
+                    let Some((a, b)) = clip_segment(proj, a, b) else { continue; };
+                    if let (Some(a), Some(b)) = (screen_point(proj, a), screen_point(proj, b)) {
+                        prims.extend(Prim::line(a, b, color, proj.width, proj.height));
+                    }
+                }
+            }
+        }
+    }
+    prims
+}
+
+/// Punto de cámara a pantalla (x, y subpíxel, z de cámara)
+fn screen_point(proj: &Projector, c: Vec3) -> Option<Vec3> {
+    proj.project_f(c).map(|(x, y)| Vec3::new(x, y, c.z))
+}
+
+/// Punto de `a`→`b` sobre el plano z = `plane` (justo sobre él, para que
+/// `project_f` no lo descarte por redondeo)
+fn cut_at(a: Vec3, b: Vec3, plane: f32) -> Vec3 {
+    let t = (plane - a.z) / (b.z - a.z);
+    let p = Vec3::lerp(a, b, t);
+    Vec3::new(p.x, p.y, plane)
+}
+
+/// Recorta un polígono convexo de cámara a z_near <= z <= z_far
+/// (Sutherland–Hodgman con los dos planos)
+fn clip_polygon(proj: &Projector, poly: &[Vec3]) -> Vec<Vec3> {
+    let mut out = poly.to_vec();
+    for (plane, keep) in [(proj.z_near, 1.0f32), (proj.z_far, -1.0)] {
+        let inside = |p: Vec3| (p.z - plane) * keep >= 0.0;
+        let input = std::mem::take(&mut out);
+        for (k, &cur) in input.iter().enumerate() {
+            let prev = input[(k + input.len() - 1) % input.len()];
+            match (inside(prev), inside(cur)) {
+                (true, true) => out.push(cur),
+                (true, false) => out.push(cut_at(prev, cur, plane)),
+                (false, true) => {
+                    out.push(cut_at(prev, cur, plane));
+                    out.push(cur);
+                }
+                (false, false) => {}
+            }
+        }
+    }
+    out
+}
+
+/// Recorta un segmento de cámara a z_near <= z <= z_far; None si queda fuera
+fn clip_segment(proj: &Projector, mut a: Vec3, mut b: Vec3) -> Option<(Vec3, Vec3)> {
+    for (plane, keep) in [(proj.z_near, 1.0f32), (proj.z_far, -1.0)] {
+        let (ia, ib) = ((a.z - plane) * keep >= 0.0, (b.z - plane) * keep >= 0.0);
+        match (ia, ib) {
+            (true, true) => {}
+            (true, false) => b = cut_at(a, b, plane),
+            (false, true) => a = cut_at(a, b, plane),
+            (false, false) => return None,
+        }
+    }
+    Some((a, b))
+}
@@ -0,0 +1,185 @@
+use crate::math::Vec3;
+use crate::bodies::orbit::Orbit;
+
+/// Parámetros de la simulación gravitatoria (sección [nbody] del sistema)
+#[derive(Clone, Copy, Debug)]
+pub struct NBodyParams {
+    /// Constante de gravitación en unidades de la escena
+    pub g: f32,
+    /// Paso fijo de integración (s); cada frame se parte en sub-pasos
+    pub sub_step: f32,
+    /// Suavizado de la fuerza a distancias cortas (evita explosiones)
+    pub softening: f32,
+}
+
+impl Default for NBodyParams {
+    fn default() -> Self {
+        Self { g: 1.0, sub_step: 0.002, softening: 1.0 }
+    }
+}
+
+/// Estado N-cuerpos integrado con velocity Verlet (simpléctico y reversible).
+///
+/// Los índices son los mismos que en `Scene::bodies`.
+pub struct NBodySim {
+    pub params: NBodyParams,
+    pub mass: Vec<f32>,
+    pub pos: Vec<Vec3>,
+    pub vel: Vec<Vec3>,
+    acc: Vec<Vec3>,
+    /// Tiempo simulado desde que se creó
+    pub time: f32,
+    /// Tiempo pedido que se descartó por no llegar con los sub-pasos (s); si
+    /// crece, la simulación va atrasada respecto del reloj
+    pub dropped: f32,
+    accum: f32,
+    e0: f64,
+}
+
+// tope de sub-pasos por frame para no congelar la app con dt grandes
+const MAX_SUBSTEPS: usize = 20_000;
+
+impl NBodySim {
+    pub fn new(params: NBodyParams, mass: Vec<f32>, pos: Vec<Vec3>, vel: Vec<Vec3>) -> Self {
+        let n = pos.len();
+        let mut sim = Self {
+            params,
+            mass,
+            pos,
+            vel,
+            acc: vec![Vec3::new(0.0, 0.0, 0.0); n],
+            time: 0.0,
+            dropped: 0.0,
+            accum: 0.0,
+            e0: 0.0,
+        };
+        sim.remove_drift();
+        sim.compute_acc();
+        sim.e0 = sim.energy();
+        sim
+    }
+
+    /// Pasa a un marco donde el momento total es cero (el sistema no deriva)
+    fn remove_drift(&mut self) {
+        let total: f32 = self.mass.iter().sum();
+        if total <= 0.0 {
+            return;
+        }
+        let mut p = Vec3::new(0.0, 0.0, 0.0);
+        for (m, v) in self.mass.iter().zip(&self.vel) {
+            p = p.add(v.mul(*m));
+        }
+        let v_cm = p.mul(1.0 / total);
+        for v in &mut self.vel {
+            *v = v.sub(v_cm);
+        }
+    }
+
+    fn compute_acc(&mut self) {
+        let g = self.params.g;
+        let eps2 = self.params.softening * self.params.softening;
+        let n = self.pos.len();
+        for a in &mut self.acc {
+            *a = Vec3::new(0.0, 0.0, 0.0);
+        }
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let d = self.pos[j].sub(self.pos[i]);
+                let r2 = d.dot(d) + eps2;
+                let inv_r3 = 1.0 / (r2 * r2.sqrt());
+                self.acc[i] = self.acc[i].add(d.mul(g * self.mass[j] * inv_r3));
+                self.acc[j] = self.acc[j].sub(d.mul(g * self.mass[i] * inv_r3));
+            }
+        }
+    }
+
+    /// Un sub-paso de velocity Verlet (h puede ser negativo: va hacia atrás)
+    fn verlet(&mut self, h: f32) {
+        for (v, a) in self.vel.iter_mut().zip(&self.acc) {
+            *v = v.add(a.mul(0.5 * h));
+        }
+        for (p, v) in self.pos.iter_mut().zip(&self.vel) {
+            *p = p.add(v.mul(h));
+        }
+        self.compute_acc();
+        for (v, a) in self.vel.iter_mut().zip(&self.acc) {
+            *v = v.add(a.mul(0.5 * h));
+        }
+        self.time += h;
+    }
+
+    /// Avanza `dt` segundos en sub-pasos fijos; lo que sobra queda para el
+    /// próximo frame, así el resultado no depende de los fps
+    pub fn step(&mut self, dt: f32) {
+        let h = self.params.sub_step.max(1e-5);
+        self.accum += dt;
+        let mut n = 0;
+        while self.accum.abs() >= h && n < MAX_SUBSTEPS {
+            let s = h.copysign(self.accum);
+            self.verlet(s);
+            self.accum -= s;
+            n += 1;
+        }
+        if n == MAX_SUBSTEPS {
+            // no se alcanzó: se descarta el atraso en vez de acumularlo, pero
+            // queda anotado
+            self.dropped += self.accum.abs();
+            self.accum = 0.0;
+        }
+    }
+
+    /// Energía total (cinética + potencial suavizado)
+    pub fn energy(&self) -> f64 {
+        let g = self.params.g as f64;
+        let eps2 = (self.params.softening * self.params.softening) as f64;
+        let mut e = 0.0f64;
+        for (m, v) in self.mass.iter().zip(&self.vel) {
+            e += 0.5 * *m as f64 * v.dot(*v) as f64;
+        }
+        let n = self.pos.len();
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let d = self.pos[j].sub(self.pos[i]);
+                let r = (d.dot(d) as f64 + eps2).sqrt();
+                e -= g * self.mass[i] as f64 * self.mass[j] as f64 / r;
+            }
+        }
+        e
+    }
+
+    /// Deriva relativa de energía desde el inicio, (E - E0) / |E0|
+    pub fn energy_drift(&self) -> f64 {
+        if self.e0 == 0.0 {
+            return 0.0;
+        }
+        (self.energy() - self.e0) / self.e0.abs()
+    }
+
+    /// Centro de masa de los cuerpos `group`: (posición, velocidad, masa)
+    pub fn center_of_mass(&self, group: &[usize]) -> (Vec3, Vec3, f32) {
+        let (mut p, mut v, mut m) = (Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), 0.0);
+        for &j in group {
+            p = p.add(self.pos[j].mul(self.mass[j]));
+            v = v.add(self.vel[j].mul(self.mass[j]));
+            m += self.mass[j];
+        }
+        if m <= 0.0 {
+            return (p, v, 0.0);
+        }
+        (p.mul(1.0 / m), v.mul(1.0 / m), m)
+    }
+
+    /// Órbita osculante del cuerpo `i` alrededor del centro de masa de
+    /// `group` (el padre, o las estrellas de una binaria) con el estado
+    /// actual. Devuelve también ese centro; None si va en hipérbola o está
+    /// encima de él.
+    pub fn osculating_orbit(&self, i: usize, group: &[usize]) -> Option<(Vec3, Orbit)> {
+        let (c, cv, m) = self.center_of_mass(group);
+        if m <= 0.0 {
+            return None;
+        }
+        let mu = self.params.g * (m + self.mass[i]);
+        let orbit = Orbit::from_state(self.pos[i].sub(c), self.vel[i].sub(cv), mu)?;
+        Some((c, orbit))
+    }
+}
@@ -0,0 +1,152 @@
+use crate::math::Vec3;
+
+/// Ruido de gradiente 3D (Perlin "mejorado") con tabla de permutación
+/// sembrada, más las variantes fractales que usan los shaders.
+/// Todas las funciones devuelven valores más o menos en [-1, 1] salvo
+/// `ridged` y `turbulence`, que van en [0, 1].
+#[derive(Clone)]
+pub struct Noise {
+    perm: [u8; 512],
+}
+
+fn xorshift(s: &mut u32) -> u32 {
+    let mut x = *s;
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    *s = x;
+    x
+}
+
+/// Semilla estable a partir de un texto (FNV-1a), p. ej. el nombre del cuerpo
+pub fn seed_from_str(s: &str) -> u32 {
+    let mut h: u32 = 0x811C9DC5;
+    for b in s.bytes() {
+        h ^= b as u32;
+        h = h.wrapping_mul(0x01000193);
+    }
+    h
+}
+
+#[inline]
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[inline]
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Producto con uno de los 12 gradientes de las aristas del cubo
+#[inline]
+fn grad(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+impl Noise {
+    /// Tabla barajada con `seed` (Fisher–Yates); misma semilla, mismo ruido
+    pub fn new(seed: u32) -> Self {
+        let mut p = [0u8; 256];
+        for (i, v) in p.iter_mut().enumerate() {
+            *v = i as u8;
+        }
+        let mut s = seed ^ 0x9E3779B9;
+        if s == 0 { s = 1; }
+        for i in (1..256).rev() {
+            let j = (xorshift(&mut s) % (i as u32 + 1)) as usize;
+            p.swap(i, j);
+        }
+        let mut perm = [0u8; 512];
+        for (i, v) in perm.iter_mut().enumerate() {
+            *v = p[i & 255];
+        }
+        Self { perm }
+    }
+
+    /// Perlin 3D en [-1, 1] (aprox.)
+    pub fn perlin(&self, p: Vec3) -> f32 {
+        let (fx, fy, fz) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let xi = (fx as i32 & 255) as usize;
+        let yi = (fy as i32 & 255) as usize;
+        let zi = (fz as i32 & 255) as usize;
+        let (x, y, z) = (p.x - fx, p.y - fy, p.z - fz);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let pm = &self.perm;
+        let a = pm[xi] as usize + yi;
+        let aa = pm[a] as usize + zi;
+        let ab = pm[a + 1] as usize + zi;
+        let b = pm[xi + 1] as usize + yi;
+        let ba = pm[b] as usize + zi;
+        let bb = pm[b + 1] as usize + zi;
+
+        lerp(
+            lerp(
+                lerp(grad(pm[aa], x, y, z), grad(pm[ba], x - 1.0, y, z), u),
+                lerp(grad(pm[ab], x, y - 1.0, z), grad(pm[bb], x - 1.0, y - 1.0, z), u),
+                v,
+            ),
+            lerp(
+                lerp(grad(pm[aa + 1], x, y, z - 1.0), grad(pm[ba + 1], x - 1.0, y, z - 1.0), u),
+                lerp(grad(pm[ab + 1], x, y - 1.0, z - 1.0), grad(pm[bb + 1], x - 1.0, y - 1.0, z - 1.0), u),
+                v,
+            ),
+            w,
+        )
+    }
+
+    /// Fractal Brownian Motion: suma de octavas, cada una al doble de
+    /// frecuencia y a `gain` de amplitud. Normalizado a [-1, 1].
+    pub fn fbm(&self, p: Vec3, octaves: u32, gain: f32) -> f32 {
+        let (mut sum, mut amp, mut norm, mut q) = (0.0, 1.0, 0.0, p);
+        for _ in 0..octaves {
+            sum += self.perlin(q) * amp;
+            norm += amp;
+            amp *= gain;
+            // un corrimiento por octava evita que los ceros se alineen en el origen
+            q = q.mul(2.0).add(Vec3::new(17.3, -9.1, 5.7));
+        }
+        sum / norm.max(1e-6)
+    }
+
+    /// Multifractal "ridged": crestas finas donde el ruido cruza por cero.
+    /// Cada octava se pesa con la anterior, así las crestas se ramifican.
+    pub fn ridged(&self, p: Vec3, octaves: u32, gain: f32) -> f32 {
+        let (mut sum, mut amp, mut norm, mut weight, mut q) = (0.0, 1.0, 0.0, 1.0, p);
+        for _ in 0..octaves {
+            let r = 1.0 - self.perlin(q).abs();
+            let r = r * r * weight;
+            weight = r.clamp(0.0, 1.0);
+            sum += r * amp;
+            norm += amp;
+            amp *= gain;
+            q = q.mul(2.0).add(Vec3::new(-4.2, 11.8, 3.3));
+        }
+        (sum / norm.max(1e-6)).clamp(0.0, 1.0)
+    }
+
+    /// Turbulencia: FBM del valor absoluto (nubes, gas revuelto) en [0, 1]
+    pub fn turbulence(&self, p: Vec3, octaves: u32, gain: f32) -> f32 {
+        let (mut sum, mut amp, mut norm, mut q) = (0.0, 1.0, 0.0, p);
+        for _ in 0..octaves {
+            sum += self.perlin(q).abs() * amp;
+            norm += amp;
+            amp *= gain;
+            q = q.mul(2.0).add(Vec3::new(7.7, 2.9, -13.1));
+        }
+        sum / norm.max(1e-6)
+    }
+
+    /// Domain warping: desplaza `p` con tres FBM independientes.
+    /// Alimentar otro ruido con el resultado da formas retorcidas.
+    pub fn warp(&self, p: Vec3, strength: f32, octaves: u32) -> Vec3 {
+        let dx = self.fbm(p, octaves, 0.5);
+        let dy = self.fbm(p.add(Vec3::new(5.2, 1.3, -2.8)), octaves, 0.5);
+        let dz = self.fbm(p.add(Vec3::new(-1.7, 9.2, 4.1)), octaves, 0.5);
+        p.add(Vec3::new(dx, dy, dz).mul(strength))
+    }
+}
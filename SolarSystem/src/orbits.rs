@@ -1,23 +1,38 @@
 use crate::math::Vec3;
-use crate::draw::draw_line;
+use crate::draw::{Band, Prim};
 use crate::projector::Projector;
 use crate::camera::Camera;
+use crate::bodies::orbit::Orbit;
 
 pub fn draw_orbit_3d(
-    buf: &mut [u32],
+    fb: &mut Band,
     cam: &Camera,
     proj: &Projector,
-    radius: f32,
-    w: usize,
-    h: usize,
+    center: Vec3,
+    orbit: &Orbit,
     color: u32,
 ) {
+    for p in orbit_prims(cam, proj, center, orbit, color) {
+        p.draw(fb);
+    }
+}
+
+/// Los segmentos de la órbita ya proyectados, en el orden en que se dibujan
+pub fn orbit_prims(
+    cam: &Camera,
+    proj: &Projector,
+    center: Vec3,
+    orbit: &Orbit,
+    color: u32,
+) -> Vec<Prim> {
     let segs = 200;
-    let mut prev: Option<(i32, i32)> = None;
+    let mut prims = Vec::with_capacity(segs);
+    let mut prev: Option<Vec3> = None;
 
     for i in 0..=segs {
-        let a = (i as f32 / segs as f32) * std::f32::consts::PI * 2.0;
-        let p = Vec3::new(radius * a.cos(), 0.0, radius * a.sin());
+        // pasos iguales en anomalía excéntrica: más puntos cerca del periapsis
+        let ea = (i as f32 / segs as f32) * std::f32::consts::PI * 2.0;
+        let p = center.add(orbit.point_at_eccentric(ea));
         let cp = Projector::world_to_camera(p, cam);
 
         if cp.z <= proj.z_near || cp.z >= proj.z_far {
@@ -25,13 +40,15 @@ pub fn draw_orbit_3d(
             continue;
         }
 
-        if let Some((sx, sy)) = proj.project(cp) {
+        if let Some((sx, sy)) = proj.project_f(cp) {
+            let s = Vec3::new(sx, sy, cp.z);
             if let Some(pr) = prev {
-                draw_line(buf, w, h, pr.0, pr.1, sx, sy, color);
+                prims.extend(Prim::line(pr, s, color, proj.width, proj.height));
             }
-            prev = Some((sx,sy));
+            prev = Some(s);
         } else {
             prev = None;
         }
     }
+    prims
 }
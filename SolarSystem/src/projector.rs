@@ -22,15 +22,67 @@ impl Projector {
         Vec3::new(rel.dot(right), rel.dot(up), rel.dot(fwd))
     }
 
+    /// Rota una dirección de mundo a cámara (sin trasladar)
+    pub fn dir_to_camera(d: Vec3, cam: &Camera) -> Vec3 {
+        let (right, up, fwd) = cam.basis();
+        Vec3::new(d.dot(right), d.dot(up), d.dot(fwd))
+    }
+
     pub fn project(&self, cam_p: Vec3) -> Option<(i32, i32)> {
+        self.project_f(cam_p).map(|(x, y)| (x as i32, y as i32))
+    }
+
+    /// Igual que `project` pero sin truncar (precisión subpíxel para triángulos)
+    pub fn project_f(&self, cam_p: Vec3) -> Option<(f32, f32)> {
         if cam_p.z < self.z_near || cam_p.z > self.z_far { return None; }
         let sx = (cam_p.x * self.fy) / cam_p.z;
         let sy = (cam_p.y * self.fy) / cam_p.z;
-        let x = (sx + (self.width as f32)*0.5) as i32;
-        let y = ((self.height as f32)*0.5 - sy) as i32;
+        let x = sx + (self.width as f32)*0.5;
+        let y = (self.height as f32)*0.5 - sy;
         Some((x,y))
     }
 
+    /// Rayo en cámara que pasa por el píxel (px, py) de pantalla; tiene z = 1,
+    /// así que el parámetro del rayo en un punto es directamente su z.
+    pub fn ray_dir(&self, px: f32, py: f32) -> Vec3 {
+        Vec3::new(
+            (px - self.width as f32 * 0.5) / self.fy,
+            (self.height as f32 * 0.5 - py) / self.fy,
+            1.0,
+        )
+    }
+
+    /// Rectángulo de pantalla (x0, y0, x1, y1, inclusivo) que cubre una esfera
+    /// de cámara con centro `c` y radio `r`. Si la esfera toca el plano de la
+    /// cámara se devuelve la pantalla entera; None si queda fuera.
+    pub fn sphere_bounds(&self, c: Vec3, r: f32) -> Option<(i32, i32, i32, i32)> {
+        if c.z + r < self.z_near || c.z - r > self.z_far { return None; }
+        let (w, h) = (self.width as i32, self.height as i32);
+
+        // pendientes x/z (o y/z) de las dos tangentes en cada eje
+        let tangents = |a: f32| -> Option<(f32, f32)> {
+            let den = c.z * c.z - r * r;
+            if c.z <= r || den <= 1e-6 { return None; }
+            let root = r * (a * a + den).sqrt();
+            Some(((a * c.z - root) / den, (a * c.z + root) / den))
+        };
+        let (x0, x1, y0, y1) = match (tangents(c.x), tangents(c.y)) {
+            (Some((kx0, kx1)), Some((ky0, ky1))) => {
+                let hw = self.width as f32 * 0.5;
+                let hh = self.height as f32 * 0.5;
+                (
+                    (hw + kx0 * self.fy).floor() as i32,
+                    (hw + kx1 * self.fy).ceil() as i32,
+                    (hh - ky1 * self.fy).floor() as i32,
+                    (hh - ky0 * self.fy).ceil() as i32,
+                )
+            }
+            _ => (0, w - 1, 0, h - 1),
+        };
+        if x1 < 0 || y1 < 0 || x0 >= w || y0 >= h { return None; }
+        Some((x0.max(0), y0.max(0), x1.min(w - 1), y1.min(h - 1)))
+    }
+
     pub fn radius_world_to_px(&self, radius_world: f32, z: f32) -> i32 {
         if z <= self.z_near { return 0; }
         ((self.fy * radius_world) / z).max(1.0) as i32
@@ -0,0 +1,387 @@
+use std::borrow::Cow;
+
+use crate::atmosphere::draw_atmosphere;
+use crate::bands::{Job, default_threads, run_banded};
+use crate::bloom::apply_bloom;
+use crate::bodies::rings::Rings;
+use crate::camera::Camera;
+use crate::draw::{BG, FrameBuffer, draw_disc_z};
+use crate::eclipse::{Occluder, sun_visibility};
+use crate::hud::reticle;
+use crate::math::Vec3;
+use crate::orbits::orbit_prims;
+use crate::noise::Noise;
+use crate::projector::Projector;
+use crate::ring_render::{draw_rings, ring_transmission};
+use crate::scene::Scene;
+use crate::material::PlanetMaterial;
+use crate::shading::{PlanetKind, draw_shaded_sphere};
+use crate::ship::Ship;
+use crate::skybox::draw_stars;
+use crate::star::{CORONA_EXTENT, Light, blackbody, draw_corona, light_color, luminosity};
+
+/// Rasterizador por software de una `Scene`.
+///
+/// Es dueño del color + z-buffer; `render` deja el resultado en `fb.color` y
+/// `render_into` lo copia a un buffer 0xRRGGBB del llamador.
+pub struct Renderer {
+    pub fb: FrameBuffer,
+    pub proj: Projector,
+    /// Modo bonito (shaders por píxel + skybox) o rápido (discos planos)
+    pub pretty_mode: bool,
+    /// Nave que sigue a la cámara, si se quiere
+    pub ship: Option<Ship>,
+    /// Retícula en el centro de la pantalla
+    pub show_hud: bool,
+    /// Halo alrededor de lo que emite luz (solo en modo bonito)
+    pub bloom: bool,
+    /// Hilos para rasterizar (el frame sale igual con cualquier cantidad)
+    pub threads: usize,
+}
+
+struct DrawItem<'a> {
+    /// centro en cámara
+    center: Vec3,
+    r_world: f32,
+    /// el de la escena; el de una estrella va teñido por su temperatura
+    material: Cow<'a, PlanetMaterial>,
+    /// color de cuerpo negro si es una estrella
+    star_color: Option<Vec3>,
+    /// color del modo rápido si el archivo lo fija
+    flat_color: Option<u32>,
+    /// estrellas que lo iluminan, direcciones en cámara (vacío si es una estrella)
+    lights: Vec<Light>,
+    /// para cada luz de `lights`, lo que puede taparla
+    shadows: Vec<StarShadow>,
+    /// ejes locales del cuerpo en cámara
+    axes: (Vec3, Vec3, Vec3),
+    noise: Noise,
+    rings: Option<&'a Rings>,
+    /// normal del plano de los anillos en cámara
+    ring_normal: Vec3,
+}
+
+/// Una estrella vista desde un cuerpo, para sus eclipses (en cámara)
+struct StarShadow {
+    center: Vec3,
+    radius: f32,
+    /// cuerpos que pueden taparla (ya filtrados)
+    occluders: Vec<Occluder>,
+}
+
+/// Las esferas de `spheres` (menos la propia, `self_idx`, y la estrella,
+/// `star_idx`) que pueden hacerle sombra a un cuerpo en `center`: las que
+/// quedan entre él y la estrella, cerca de la recta que los une (incluida
+/// la penumbra).
+fn shadow_casters(
+    spheres: &[(usize, Occluder)],
+    self_idx: usize,
+    star_idx: usize,
+    center: Vec3,
+    radius: f32,
+    sun: Vec3,
+    sun_radius: f32,
+) -> Vec<Occluder> {
+    let axis = sun.sub(center);
+    let sun_dist = axis.len();
+    if sun_dist <= 1e-6 { return Vec::new(); }
+    let u = axis.mul(1.0 / sun_dist);
+    spheres
+        .iter()
+        .filter(|(j, o)| {
+            if *j == self_idx || *j == star_idx { return false; }
+            let rel = o.center.sub(center);
+            let along = rel.dot(u);
+            if along < -(o.radius + radius) || along > sun_dist { return false; }
+            let perp = rel.sub(u.mul(along)).len();
+            perp < o.radius + radius + sun_radius * along.max(0.0) / sun_dist
+        })
+        .map(|(_, o)| *o)
+        .collect()
+}
+
+impl Renderer {
+    /// Renderer de `width`×`height` con FOV vertical de 60° y sin nave ni HUD
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            fb: FrameBuffer::new(width, height),
+            proj: Projector::new(width, height, 60.0, 0.1, 5000.0),
+            pretty_mode: true,
+            ship: None,
+            show_hud: false,
+            bloom: true,
+            threads: default_threads(),
+        }
+    }
+
+    pub fn width(&self) -> usize { self.fb.w }
+
+    pub fn height(&self) -> usize { self.fb.h }
+
+    /// Dibuja la escena vista desde `cam` en el tiempo `t` (segundos).
+    ///
+    /// Primero se arma la lista de lo que hay que dibujar, cada cosa con las
+    /// filas de pantalla que ocupa, y después se rasteriza por franjas en
+    /// paralelo (`bands::run_banded`); el bloom va entre medio sobre el frame
+    /// entero porque la nave se dibuja encima de él.
+    pub fn render(&mut self, scene: &Scene, cam: &Camera, t: f32) -> &[u32] {
+        let proj = &self.proj;
+        let fb = &mut self.fb;
+        // filas que cubre una esfera de cámara
+        let rows = |c: Vec3, r: f32| proj.sphere_bounds(c, r).map(|(_, y0, _, y1)| (y0, y1));
+
+        // Limpiar color + profundidad
+        fb.clear(BG);
+
+        let positions = scene.positions(t);
+        let stars = scene.stars();
+
+
+        // Cualquier cuerpo puede tapar una estrella (incluso otra estrella),
+        // esté o no en pantalla
+        let spheres: Vec<(usize, Occluder)> = scene
+            .bodies
+            .iter()
+            .zip(&positions)
+            .enumerate()
+            .map(|(i, (sb, &ws))| {
+                (i, Occluder { center: Projector::world_to_camera(ws, cam), radius: sb.body.radius })
+            })
+            .collect();
+        // luminosidad de cada estrella
+        let star_power: Vec<f32> = stars
+            .iter()
+            .map(|&s| luminosity(scene.bodies[s].body.radius, scene.bodies[s].body.temperature))
+            .collect();
+
+        // Proyección de los cuerpos
+        let mut items: Vec<DrawItem> = Vec::new();
+        for (i, (sb, &ws)) in scene.bodies.iter().zip(&positions).enumerate() {
+            let cp = Projector::world_to_camera(ws, cam);
+            let r = sb.body.radius;
+            let is_star = sb.material.kind == PlanetKind::Star;
+            // la corona de una estrella sobresale bastante del disco
+            let reach = if is_star { r * CORONA_EXTENT } else { r };
+            if cp.z + reach > proj.z_near && cp.z - reach < proj.z_far {
+                let star_color = is_star.then(|| blackbody(sb.body.temperature));
+
+                // a cada cuerpo le llega en total la misma luz (sin caída con
+                // la distancia, como siempre); se reparte entre las estrellas
+                // según luminosidad / distancia²
+                let mut lights = Vec::new();
+                let mut shadows = Vec::new();
+                if !is_star {
+                    let weight = |k: usize| {
+                        let d = positions[stars[k]].sub(ws);
+                        star_power[k] / d.dot(d).max(1e-6)
+                    };
+                    let total: f32 = (0..stars.len()).map(weight).sum();
+                    for (k, &s) in stars.iter().enumerate() {
+                        if total <= 0.0 { break; }
+                        let share = weight(k) / total;
+                        if share < 1e-3 { continue; }
+                        let star = &scene.bodies[s].body;
+                        let sun_cam = spheres[s].1.center;
+                        lights.push(Light {
+                            dir: Projector::dir_to_camera(positions[s].sub(ws).norm(), cam),
+                            color: light_color(star.temperature).mul(share),
+                        });
+                        shadows.push(StarShadow {
+                            center: sun_cam,
+                            radius: star.radius,
+                            occluders: shadow_casters(&spheres, i, s, cp, r, sun_cam, star.radius),
+                        });
+                    }
+                }
+
+                items.push(DrawItem {
+                    center: cp,
+                    r_world: r,
+                    material: match star_color {
+                        Some(c) => Cow::Owned(sb.material.tinted(c)),
+                        None => Cow::Borrowed(&sb.material),
+                    },
+                    star_color,
+                    flat_color: sb.body.color,
+                    lights,
+                    shadows,
+                    axes: {
+                        let (ax, ay, az) = sb.body.axes(t);
+                        let to_cam = |d| Projector::dir_to_camera(d, cam);
+                        (to_cam(ax), to_cam(ay), to_cam(az))
+                    },
+                    noise: Noise::new(sb.body.seed),
+                    rings: sb.body.rings.as_ref(),
+                    ring_normal: Projector::dir_to_camera(sb.body.ring_normal(), cam),
+                });
+            }
+        }
+
+        // El z-buffer resuelve la visibilidad; de cerca a lejos para que
+        // los píxeles tapados se descarten antes de correr el shader
+        items.sort_by(|a, b| a.center.z.partial_cmp(&b.center.z).unwrap());
+
+        let mut jobs: Vec<Job> = Vec::new();
+
+        // Skybox solo en modo bonito (para ahorrar CPU en modo rápido)
+        if self.pretty_mode {
+            jobs.push(Job::everywhere(|b| draw_stars(b, cam, proj, &scene.stars)));
+        }
+
+        // Órbitas (las lunas alrededor de su padre, que se mueve), segmento a segmento
+        for (i, sb) in scene.bodies.iter().enumerate() {
+            if sb.body.draw_orbit
+                && let Some((center, orbit)) = scene.orbit_path(i, &positions)
+            {
+                jobs.extend(orbit_prims(cam, proj, center, &orbit, scene.orbit_color).into_iter().map(Job::prim));
+            }
+        }
+
+        // Dibujar según modo
+        for it in &items {
+            if self.pretty_mode {
+                // modo bonito: shader por píxel, con sombras de anillos y eclipses
+                let Some(rows) = rows(it.center, it.r_world) else { continue; };
+                jobs.push(Job::new(rows, move |b| {
+                    let sun_visibility = |k: usize, q: Vec3| {
+                        let rings = match it.rings {
+                            Some(r) => ring_transmission(r, &it.noise, q, it.ring_normal, it.lights[k].dir),
+                            None => 1.0,
+                        };
+                        let sh = &it.shadows[k];
+                        if sh.occluders.is_empty() || rings <= 0.0 {
+                            return rings;
+                        }
+                        rings * sun_visibility(it.center.add(q), sh.center, sh.radius, &sh.occluders)
+                    };
+                    draw_shaded_sphere(
+                        b, proj, it.center, it.r_world, &it.material, &it.lights, it.axes,
+                        &it.noise, &sun_visibility, t,
+                    );
+                }));
+            } else if let Some((sx, sy)) = proj.project(it.center) {
+                // modo rápido: solo discos de color plano
+                let r_px = proj.radius_world_to_px(it.r_world, it.center.z);
+                let color = it.flat_color.unwrap_or_else(|| it.material.flat_color());
+                jobs.push(Job::new((sy - r_px, sy + r_px), move |b| {
+                    draw_disc_z(b, sx, sy, r_px, it.center.z, color);
+                }));
+            }
+        }
+
+        // Coronas, atmósferas y anillos: transparentes y sin profundidad, así
+        // que van después de todo lo opaco y de lejos a cerca para que se
+        // mezclen en orden
+        if self.pretty_mode {
+            for it in items.iter().rev() {
+                if let Some(c) = it.star_color
+                    && let Some(rows) = rows(it.center, it.r_world * CORONA_EXTENT)
+                {
+                    let m = &it.material;
+                    let (color, prominence) = (c.mul(m.emission), m.accent.mul(m.emission));
+                    jobs.push(Job::new(rows, move |b| {
+                        draw_corona(b, proj, it.center, it.r_world, color, prominence, it.axes, &it.noise, t);
+                    }));
+                }
+                if let Some(atm) = &it.material.atmosphere
+                    && let Some(rows) = rows(it.center, it.r_world + atm.height)
+                {
+                    jobs.push(Job::new(rows, move |b| {
+                        draw_atmosphere(b, proj, it.center, it.r_world, atm, &it.lights);
+                    }));
+                }
+                if let Some(rings) = it.rings
+                    && let Some(rows) = rows(it.center, rings.outer)
+                {
+                    jobs.push(Job::new(rows, move |b| {
+                        draw_rings(b, proj, it.center, it.r_world, rings, it.ring_normal, &it.lights, &it.noise);
+                    }));
+                }
+            }
+        }
+
+        run_banded(fb, self.threads, &jobs);
+
+        if self.pretty_mode && self.bloom {
+            apply_bloom(fb, 0.5);
+        }
+
+        // Nave siguiendo a la cámara, iluminada por la estrella principal
+        if let Some(ship) = &self.ship {
+            let sun = scene.primary_star().map_or(Vec3::new(0.0, 0.0, 0.0), |s| positions[s]);
+            let jobs: Vec<Job> = ship.prims(cam, proj, sun).into_iter().map(Job::prim).collect();
+            run_banded(fb, self.threads, &jobs);
+        }
+
+        // HUD (encima de todo, sin profundidad)
+        if self.show_hud {
+            reticle(&mut fb.color, fb.w, fb.h);
+        }
+
+        &self.fb.color
+    }
+
+    /// Como `render`, pero copia el frame a `out` (largo `width * height`)
+    ///
+    /// # Panics
+    ///
+    /// Si `out` no tiene exactamente `width() * height()` píxeles.
+    pub fn render_into(&mut self, scene: &Scene, cam: &Camera, t: f32, out: &mut [u32]) {
+        assert_eq!(
+            out.len(),
+            self.width() * self.height(),
+            "render_into: el buffer tiene {} píxeles y el frame es de {}x{}",
+            out.len(),
+            self.width(),
+            self.height(),
+        );
+        let frame = self.render(scene, cam, t);
+        out.copy_from_slice(frame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frame (color + bits de profundidad) del sistema por defecto con `threads` hilos
+    fn frame(cam: &Camera, pretty: bool, threads: usize) -> (Vec<u32>, Vec<u32>) {
+        let scene = Scene::default_system();
+        let mut r = Renderer::new(160, 100);
+        r.pretty_mode = pretty;
+        r.ship = Some(Ship::new());
+        r.threads = threads;
+        r.render(&scene, cam, 7.3);
+        (r.fb.color.clone(), r.fb.depth.iter().map(|z| z.to_bits()).collect())
+    }
+
+    #[test]
+    fn bands_match_single_thread() {
+        let scene = Scene::default_system();
+        let pos = scene.positions(7.3);
+        // vista general + de cerca del primer planeta (ocupa casi toda la pantalla)
+        let mut near = Camera::new();
+        let planet = scene.bodies.iter().position(|sb| sb.material.kind != PlanetKind::Star).unwrap();
+        let radius = scene.bodies[planet].body.radius;
+        near.pos = pos[planet].add(Vec3::new(0.0, 0.0, radius * 2.0));
+        near.yaw = 0.0;
+        near.pitch = 0.0;
+
+        for cam in [Camera::new(), near] {
+            for pretty in [true, false] {
+                let single = frame(&cam, pretty, 1);
+                assert!(single.0.iter().any(|&c| c != BG), "frame vacío");
+                for threads in [2, 3, 8] {
+                    assert!(frame(&cam, pretty, threads) == single, "{} hilos difieren", threads);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "el buffer tiene 10 píxeles y el frame es de 4x3")]
+    fn render_into_checks_the_buffer() {
+        let mut r = Renderer::new(4, 3);
+        r.render_into(&Scene::default_system(), &Camera::new(), 0.0, &mut [0; 10]);
+    }
+}
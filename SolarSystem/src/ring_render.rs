@@ -0,0 +1,93 @@
+use crate::bodies::rings::Rings;
+use crate::draw::{Band, rgb};
+use crate::math::Vec3;
+use crate::noise::Noise;
+use crate::projector::Projector;
+use crate::star::Light;
+
+/// Cuánta luz del Sol pasa a través de los anillos hasta el punto `q`
+/// (relativo al centro del cuerpo, en cámara). `normal` es la normal del
+/// plano de los anillos y `light` la dirección al Sol, ambas en cámara.
+pub fn ring_transmission(rings: &Rings, noise: &Noise, q: Vec3, normal: Vec3, light: Vec3) -> f32 {
+    let dn = light.dot(normal);
+    if dn.abs() < 1e-5 { return 1.0; }
+    // el rayo q + s·light cruza el plano en s; detrás del punto no cuenta
+    let s = -q.dot(normal) / dn;
+    if s <= 0.0 { return 1.0; }
+    let r = q.add(light.mul(s)).len();
+    1.0 - rings.alpha(r, noise)
+}
+
+/// 0 si el cuerpo (esfera de radio `radius` en el origen) tapa al Sol desde
+/// `q`, 1 si no; con un borde suave de un par de por ciento del radio.
+fn planet_shadow(q: Vec3, light: Vec3, radius: f32) -> f32 {
+    let s = -q.dot(light);
+    if s <= 0.0 { return 1.0; }
+    let dist = q.add(light.mul(s)).len();
+    ((dist - radius * 0.98) / (radius * 0.04)).clamp(0.0, 1.0)
+}
+
+/// Dibuja los anillos de un cuerpo con centro `center` (cámara) como un disco
+/// agujereado semitransparente. Va en la pasada transparente: el z-buffer
+/// esconde la parte que queda detrás del planeta y la de adelante se mezcla
+/// encima. Los anillos reciben la sombra del planeta (una por estrella) y se
+/// iluminan por las dos caras (la de atrás un poco menos, la luz la atraviesa).
+#[allow(clippy::too_many_arguments)]
+pub fn draw_rings(
+    fb: &mut Band,
+    proj: &Projector,
+    center: Vec3,
+    radius: f32,
+    rings: &Rings,
+    normal: Vec3,
+    lights: &[Light],
+    noise: &Noise,
+) {
+    let Some((x0, y0, x1, y1)) = proj.sphere_bounds(center, rings.outer) else { return; };
+    let Some((y0, y1)) = fb.clip_rows(y0, y1) else { return; };
+    let cn = center.dot(normal);
+    // de qué lado del plano está la cámara (origen del espacio de cámara)
+    let cam_side = (-cn).signum();
+    // cuánto ilumina cada estrella la cara que se ve
+    let strength: Vec<Vec3> = lights
+        .iter()
+        .map(|l| {
+            let sun_side = l.dir.dot(normal).signum();
+            let face = if cam_side == sun_side { 1.0 } else { 0.55 };
+            let lit = 0.25 + 0.75 * l.dir.dot(normal).abs().sqrt();
+            l.color.mul(face * lit)
+        })
+        .collect();
+
+    for yy in y0..=y1 {
+        for xx in x0..=x1 {
+            let d = proj.ray_dir(xx as f32 + 0.5, yy as f32 + 0.5);
+            let dn = d.dot(normal);
+            if dn.abs() < 1e-6 { continue; }
+            // d.z = 1: el parámetro del rayo es la z de cámara
+            let z = cn / dn;
+            if z < proj.z_near || z > proj.z_far { continue; }
+            let i = fb.index(xx, yy);
+            if z >= fb.depth[i] { continue; }
+
+            let q = d.mul(z).sub(center);
+            let r = q.len();
+            let alpha = rings.alpha(r, noise);
+            if alpha <= 0.003 { continue; }
+
+            let mut shade = Vec3::new(0.0, 0.0, 0.0);
+            for (l, s) in lights.iter().zip(&strength) {
+                shade = shade.add(s.mul(planet_shadow(q, l.dir, radius)));
+            }
+            let c = rings.color(r);
+            let col = Vec3::new(c.x * shade.x, c.y * shade.y, c.z * shade.z);
+
+            let dst = fb.color[i];
+            let ch = |sh: u32, c: f32| {
+                let under = ((dst >> sh) & 0xFF) as f32 / 255.0;
+                ((under * (1.0 - alpha) + c * alpha).clamp(0.0, 1.0) * 255.0) as u8
+            };
+            fb.color[i] = rgb(ch(16, col.x), ch(8, col.y), ch(0, col.z));
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use crate::math::Vec3;
+use crate::bake::bake_surface;
+use crate::camera::Camera;
+use crate::bodies::body::Body;
+use crate::draw::rgb;
+use crate::bodies::orbit::Orbit;
+use crate::nbody::{NBodyParams, NBodySim};
+use crate::material::PlanetMaterial;
+use crate::shading::PlanetKind;
+use crate::skybox::make_stars;
+use crate::star::luminosity;
+use crate::system_file::{DEFAULT_SYSTEM, parse_system};
+
+/// Un cuerpo del sistema junto con el material que lo pinta.
+#[derive(Clone)]
+pub struct SceneBody {
+    pub body: Body,
+    pub material: PlanetMaterial,
+}
+
+/// Todo lo que se dibuja en un frame, salvo la cámara.
+///
+/// Los cuerpos `star` son las fuentes de luz (puede haber varias). Los cuerpos
+/// forman un árbol por `Body::parent`; un padre siempre va antes que sus hijos
+/// en `bodies`, así las posiciones salen en una sola pasada. Los cuerpos raíz
+/// orbitan el origen, que es el baricentro del sistema: una estrella sola va
+/// quieta ahí y las de una binaria giran alrededor de él.
+///
+/// El movimiento es analítico (función de `t`) salvo que haya una simulación
+/// N-cuerpos activa en `sim`; en ese caso manda su estado y `t` se ignora.
+pub struct Scene {
+    pub bodies: Vec<SceneBody>,
+    pub nbody: NBodyParams,
+    pub sim: Option<NBodySim>,
+    /// Direcciones unitarias de las estrellas del skybox
+    pub stars: Vec<Vec3>,
+    pub orbit_color: u32,
+}
+
+impl Scene {
+    /// Escena vacía con un skybox de `star_count` estrellas
+    pub fn new(star_count: usize, star_seed: u32) -> Self {
+        Self {
+            bodies: Vec::new(),
+            nbody: NBodyParams::default(),
+            sim: None,
+            stars: make_stars(star_count, star_seed),
+            orbit_color: rgb(40, 40, 70),
+        }
+    }
+
+    /// Agrega un cuerpo y devuelve su índice (para usarlo como `parent`).
+    /// El padre, si hay, ya tiene que estar en la escena. Un `PlanetKind`
+    /// suelto sirve como material (usa su preset).
+    pub fn add(&mut self, body: Body, material: impl Into<PlanetMaterial>) -> usize {
+        let material = material.into();
+        if let Some(p) = body.parent {
+            assert!(p < self.bodies.len(), "{}: el padre #{} no existe todavía", body.name, p);
+        }
+        self.bodies.push(SceneBody { body, material });
+        self.bodies.len() - 1
+    }
+
+    /// El sistema de demo: Sol + 4 planetas (`systems/default.toml`)
+    pub fn default_system() -> Self {
+        parse_system(DEFAULT_SYSTEM).expect("systems/default.toml inválido")
+    }
+
+    /// Hornea a mapas de `width` de ancho la superficie estática de los
+    /// cuerpos que la tienen (`PlanetKind::is_static`); los demás siguen
+    /// procedurales, repartiendo cada uno entre `threads` hilos. Devuelve
+    /// cuántos horneó.
+    pub fn bake_surfaces(&mut self, width: usize, threads: usize) -> usize {
+        let mut baked = 0;
+        for sb in &mut self.bodies {
+            if let Some(maps) = bake_surface(&sb.material, sb.body.seed, width, threads) {
+                sb.material.maps = Some(Arc::new(maps));
+                baked += 1;
+            }
+        }
+        baked
+    }
+
+    /// Índices de las estrellas, en el orden de `bodies`
+    pub fn stars(&self) -> Vec<usize> {
+        self.bodies
+            .iter()
+            .enumerate()
+            .filter(|(_, sb)| sb.material.kind == PlanetKind::Star)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// La estrella más luminosa (None si no hay ninguna)
+    pub fn primary_star(&self) -> Option<usize> {
+        self.stars().into_iter().max_by(|&a, &b| {
+            let l = |i: usize| luminosity(self.bodies[i].body.radius, self.bodies[i].body.temperature);
+            l(a).total_cmp(&l(b))
+        })
+    }
+
+    /// Posición en mundo de la estrella más luminosa (origen si no hay)
+    pub fn sun_pos(&self, t: f32) -> Vec3 {
+        match self.primary_star() {
+            Some(i) => self.positions(t)[i],
+            None => Vec3::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Estrellas raíz: las que giran alrededor del baricentro del sistema
+    fn root_stars(&self) -> Vec<usize> {
+        self.stars().into_iter().filter(|&i| self.bodies[i].body.parent.is_none()).collect()
+    }
+
+    /// Posiciones en mundo de todos los cuerpos en el tiempo `t`, en el
+    /// orden de `bodies` (cada hijo se suma a la posición de su padre)
+    pub fn positions(&self, t: f32) -> Vec<Vec3> {
+        if let Some(sim) = &self.sim {
+            return sim.pos.clone();
+        }
+        let mut out: Vec<Vec3> = Vec::with_capacity(self.bodies.len());
+        for sb in &self.bodies {
+            let parent_world = self.parent_pos(sb, &out);
+            out.push(sb.body.pos(t, parent_world));
+        }
+        out
+    }
+
+    // ---------------- Modo N-cuerpos --------------------
+
+    pub fn is_simulated(&self) -> bool {
+        self.sim.is_some()
+    }
+
+    /// Pasa a simulación gravitatoria partiendo del estado analítico en `t`:
+    /// mismas posiciones y la velocidad kepleriana de cada órbita
+    pub fn start_nbody(&mut self, t: f32) {
+        let g = self.nbody.g;
+        let pos = self.positions(t);
+        let mass: Vec<f32> = self.bodies.iter().map(|sb| sb.body.mass).collect();
+        let roots = self.root_stars();
+        let mut vel: Vec<Vec3> = Vec::with_capacity(pos.len());
+        for (i, sb) in self.bodies.iter().enumerate() {
+            let (parent_vel, mu) = match sb.body.parent {
+                Some(p) => (vel[p], g * (mass[p] + mass[i])),
+                None => {
+                    // alrededor del baricentro: un planeta siente a todas las
+                    // estrellas raíz; una de ellas, a las otras con la órbita
+                    // reducida a su distancia al baricentro
+                    let others: f32 = roots.iter().filter(|&&j| j != i).map(|&j| mass[j]).sum();
+                    let mu = if roots.contains(&i) {
+                        g * others * others * others / (others + mass[i]).powi(2)
+                    } else {
+                        g * (others + mass[i])
+                    };
+                    (Vec3::new(0.0, 0.0, 0.0), mu)
+                }
+            };
+            vel.push(parent_vel.add(sb.body.orbit.velocity(t, mu)));
+        }
+        self.sim = Some(NBodySim::new(self.nbody, mass, pos, vel));
+    }
+
+    /// Vuelve al movimiento analítico
+    pub fn stop_nbody(&mut self) {
+        self.sim = None;
+    }
+
+    /// Avanza la simulación (si hay) `dt` segundos de tiempo simulado
+    pub fn step(&mut self, dt: f32) {
+        if let Some(sim) = &mut self.sim {
+            sim.step(dt);
+        }
+    }
+
+    /// Órbita a dibujar para el cuerpo `i` y su centro en mundo: la analítica
+    /// alrededor del padre, o en modo N-cuerpos la osculante respecto al
+    /// padre (los cuerpos raíz, respecto a las estrellas raíz que no son él).
+    /// None si no está ligado o no tiene alrededor de qué girar.
+    pub fn orbit_path(&self, i: usize, positions: &[Vec3]) -> Option<(Vec3, Orbit)> {
+        let sb = &self.bodies[i];
+        match &self.sim {
+            None => Some((self.parent_pos(sb, positions), sb.body.orbit)),
+            Some(sim) => {
+                let group = match sb.body.parent {
+                    Some(p) => vec![p],
+                    None => self.root_stars().into_iter().filter(|&j| j != i).collect(),
+                };
+                sim.osculating_orbit(i, &group)
+            }
+        }
+    }
+
+    /// Centro de la órbita de un cuerpo, dadas las posiciones ya calculadas
+    pub fn parent_pos(&self, sb: &SceneBody, positions: &[Vec3]) -> Vec3 {
+        sb.body
+            .parent
+            .map(|p| positions[p])
+            .unwrap_or(Vec3::new(0.0, 0.0, 0.0))
+    }
+
+    /// Posiciones de los cuerpos raíz (estrella + planetas) para el warp
+    pub fn warp_targets(&self, t: f32) -> Vec<Vec3> {
+        self.positions(t)
+            .into_iter()
+            .zip(&self.bodies)
+            .filter(|(_, sb)| sb.body.parent.is_none())
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    /// Evita que la cámara entre a los cuerpos
+    pub fn resolve_collisions(&self, cam: &mut Camera, t: f32) {
+        let mut push_out = |center: Vec3, radius: f32| {
+            let diff = cam.pos.sub(center);
+            let dist = diff.len();
+            if dist <= 0.0001 {
+                return;
+            }
+            // factor para no quedar pegado tan cerca
+            let min_dist = radius * 1.3;
+            if dist < min_dist {
+                let dir = diff.mul(1.0 / dist);
+                cam.pos = center.add(dir.mul(min_dist));
+            }
+        };
+
+        for (sb, p) in self.bodies.iter().zip(self.positions(t)) {
+            // la estrella con radio algo más grande, porque es enorme visualmente
+            let k = if matches!(sb.material.kind, PlanetKind::Star) { 2.0 } else { 1.5 };
+            push_out(p, sb.body.radius * k);
+        }
+    }
+}
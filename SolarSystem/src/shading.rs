@@ -1,13 +1,29 @@
 use crate::math::Vec3;
-use crate::draw::{put_px, rgb};
-
-#[derive(Clone, Copy)]
+use crate::draw::{Band, put_px_z, rgb};
+use crate::material::{Clouds, PlanetMaterial};
+use crate::noise::Noise;
+use crate::projector::Projector;
+use crate::star::Light;
+use crate::texture::{SurfaceMaps, equirect_uv};
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PlanetKind {
     Star,
     Rocky,
     GasGiant,
     Ice,
     Volcanic,
+    /// Pintado a mano: sale de mapas equirectangulares (`PlanetMaterial::maps`)
+    Textured,
+}
+
+impl PlanetKind {
+    /// Modelos cuya superficie no cambia con el tiempo (fuera de lo que se
+    /// suma encima: lava, nubes, auroras) y se pueden hornear a mapas
+    pub fn is_static(self) -> bool {
+        matches!(self, PlanetKind::Rocky | PlanetKind::Ice | PlanetKind::Volcanic)
+    }
 }
 
 fn clamp(x: f32, a: f32, b: f32) -> f32 { x.max(a).min(b) }
@@ -16,144 +32,474 @@ fn mix3(a: Vec3, b: Vec3, t: f32) -> Vec3 {
     Vec3::new(mix(a.x, b.x, t), mix(a.y, b.y, t), mix(a.z, b.z, t))
 }
 
-// ---------------- Shaders muy baratos -----------------
+/// Lambert con un poco de luz ambiente para que el lado nocturno no sea negro
+/// puro. `diffuse` es la suma por estrella de su color por n·l.
+fn lambert(albedo: Vec3, diffuse: Vec3, ambient: f32) -> Vec3 {
+    let k = |d: f32| ambient + (1.0 - ambient) * d;
+    Vec3::new(albedo.x * k(diffuse.x), albedo.y * k(diffuse.y), albedo.z * k(diffuse.z))
+}
 
-fn shade_star(local: Vec3, n: Vec3, view: Vec3, tsec: f32) -> Vec3 {
-    let lat = local.y;
-    let lon = local.z.atan2(local.x);
+/// Paso suave de 0 a 1 entre `e0` y `e1` (con `e0 > e1` baja de 1 a 0)
+pub(crate) fn smoothstep(e0: f32, e1: f32, x: f32) -> f32 {
+    let t = clamp((x - e0) / (e1 - e0), 0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
 
-    let base = Vec3::new(1.0, 0.85, 0.45);
+// ---------------- Shaders procedurales -----------------
+//
+// `local` es el punto de la esfera unitaria en el marco del cuerpo (y = eje
+// de giro), así el ruido 3D no tiene costuras ni se estira en los polos.
+// Colores y frecuencias salen del `PlanetMaterial` del cuerpo.
+//
+// Cada shader devuelve albedo (lo que refleja, se multiplica por la luz) y
+// emisión (lo que brilla solo: lava, ciudades, la estrella entera), así el
+// lado nocturno puede seguir mostrando lo que emite.
+
+/// Lo que devuelve un shader de superficie
+#[derive(Clone, Copy)]
+pub(crate) struct Surface {
+    pub(crate) albedo: Vec3,
+    pub(crate) emission: Vec3,
+    /// Cuánto de este punto es liso y brillante (agua, hielo): 0..1
+    pub(crate) gloss: f32,
+    /// Normal para la luz si el shader la cambia (mapa de normales)
+    pub(crate) normal: Option<Vec3>,
+}
 
-    // “granulación” simple
-    let gran = (lat * 10.0 + lon * 6.0 + tsec * 4.0).sin() * 0.5 + 0.5;
-    let gran_color = mix3(base.mul(0.8), base.mul(1.2), gran);
+impl Surface {
+    fn lit(albedo: Vec3) -> Self {
+        Self { albedo, emission: Vec3::new(0.0, 0.0, 0.0), gloss: 0.0, normal: None }
+    }
+}
 
-    let pulse = 0.9 + 0.1 * (tsec * 3.0).sin();
+/// Reflejo especular GGX (Trowbridge–Reitz) con Fresnel de Schlick para agua
+/// y hielo. `light` trae la intensidad en su largo y `view` va de la cámara
+/// al punto; devuelve cuánta luz del Sol rebota hacia la cámara.
+fn specular(n: Vec3, view: Vec3, light: Vec3, roughness: f32) -> f32 {
+    let intensity = light.len();
+    if intensity <= 0.0 { return 0.0; }
+    let l = light.mul(1.0 / intensity);
+    let v = view.mul(-1.0);
+    let nl = n.dot(l);
+    let nv = n.dot(v);
+    if nl <= 0.0 || nv <= 0.0 { return 0.0; }
+    let h = l.add(v).norm();
+    let nh = clamp(n.dot(h), 0.0, 1.0);
+    let vh = clamp(v.dot(h), 0.0, 1.0);
+
+    let a = (roughness * roughness).max(1e-3);
+    let a2 = a * a;
+    let dd = nh * nh * (a2 - 1.0) + 1.0;
+    let d = a2 / (std::f32::consts::PI * dd * dd);
+    // visibilidad de Smith–Schlick ya dividida por 4·nl·nv
+    let k = a * 0.5;
+    let vis = 0.25 / ((nl * (1.0 - k) + k) * (nv * (1.0 - k) + k));
+    // reflectancia a incidencia normal del agua/hielo
+    let f = 0.02 + 0.98 * (1.0 - vh).powi(5);
+    d * vis * f * nl * intensity
+}
 
-    let mu = clamp(n.dot(view.mul(-1.0)), 0.0, 1.0);
-    let limb = mix(0.5, 1.0, mu);
+/// Cuánto "es de noche" en un punto: 1 sin Sol, 0 a plena luz. `light` trae
+/// la intensidad en su largo, así que las sombras y eclipses también cuentan.
+fn night(n: Vec3, light: Vec3) -> f32 {
+    smoothstep(0.15, 0.0, n.dot(light))
+}
 
-    gran_color.mul(pulse * limb)
+/// Casquete polar con borde irregular desde `m.ice_cap_lat`: cuánto casquete
+/// hay en `local` (0..1)
+fn cap_mask(noise: &Noise, m: &PlanetMaterial, local: Vec3) -> f32 {
+    if m.ice_cap_lat >= 1.0 { return 0.0; }
+    let edge = local.y.abs() + noise.fbm(local.mul(6.0), 3, 0.5) * 0.08;
+    smoothstep(m.ice_cap_lat, m.ice_cap_lat + 0.1, edge)
 }
 
-fn shade_rocky(local: Vec3, n: Vec3, _view: Vec3, _tsec: f32) -> Vec3 {
-    let lat = local.y;
-    let lon = local.z.atan2(local.x);
+fn polar_cap(m: &PlanetMaterial, cap: f32, col: Vec3) -> Vec3 {
+    mix3(col, m.cap_color, cap * 0.85)
+}
 
-    let rock_base = Vec3::new(0.4, 0.3, 0.22);
-    let rock_dark = Vec3::new(0.22, 0.15, 0.12);
+/// Aurora: una franja ondulante alrededor de cada polo, solo de noche
+fn aurora(noise: &Noise, m: &PlanetMaterial, local: Vec3, n: Vec3, light: Vec3, tsec: f32) -> Vec3 {
+    if m.aurora <= 0.0 { return Vec3::new(0.0, 0.0, 0.0); }
+    let wobble = noise.fbm(local.mul(4.0).add(Vec3::new(tsec * 0.3, 0.0, 0.0)), 3, 0.5) * 0.05;
+    let lat = local.y.abs() + wobble;
+    let band = smoothstep(0.78, 0.84, lat) * smoothstep(0.94, 0.86, lat);
+    let curtains = 0.5 + 0.5 * noise.fbm(local.mul(12.0).add(Vec3::new(0.0, tsec * 0.5, 0.0)), 2, 0.5);
+    m.aurora_color.mul(band * curtains * m.aurora * night(n, light))
+}
 
-    let h = (lat * 4.0 + lon * 3.0).sin() * 0.5 + 0.5;
-    let cont = mix3(rock_dark, rock_base, h);
+fn shade_star(noise: &Noise, m: &PlanetMaterial, local: Vec3, n: Vec3, view: Vec3, _light: Vec3, tsec: f32) -> Surface {
+    // supergranulación: manchones grandes que se mueven despacio
+    let flow = noise.warp(local.mul(m.noise_scale).add(Vec3::new(0.0, tsec * 0.03, tsec * 0.02)), 0.6, 2);
+    let base = mix3(m.low, m.high, noise.fbm(flow, 3, 0.55) * 0.5 + 0.5);
+
+    // granulación: celdas claras con bordes oscuros que se deshacen y vuelven
+    // a armar; se funden dos cortes del ruido separados en el tiempo
+    let phase = tsec * 0.2;
+    let slice = |k: f32| {
+        let p = local.mul(m.detail_scale).add(Vec3::new(k * 3.1, k * 1.7, -k * 2.3));
+        let cells = noise.fbm(p, 2, 0.5);
+        let lanes = noise.ridged(p.mul(0.7), 2, 0.5);
+        cells * 0.5 - smoothstep(0.7, 1.0, lanes) * 0.5
+    };
+    let k = smoothstep(0.0, 1.0, phase.fract());
+    let gran = 0.9 + 0.25 * mix(slice(phase.floor()), slice(phase.floor() + 1.0), k);
+
+    // manchas: grupos en dos franjas de latitud que giran con la estrella
+    // y cambian muy despacio; umbra oscura rodeada de penumbra
+    let belt = smoothstep(0.05, 0.15, local.y.abs()) * smoothstep(0.55, 0.4, local.y.abs());
+    let v = noise.fbm(local.mul(3.5).add(Vec3::new(41.0, 0.0, tsec * 0.004)), 4, 0.5) * 0.5 + 0.5;
+    let e0 = 0.75 - 0.25 * m.sunspots;
+    let penumbra = smoothstep(e0, e0 + 0.03, v) * belt;
+    let umbra = smoothstep(e0 + 0.05, e0 + 0.08, v) * belt;
+    let spots = 1.0 - 0.45 * penumbra - 0.45 * umbra;
+
+    // oscurecimiento al limbo, más fuerte en el azul: el borde se ve más rojo
+    let mu = clamp(n.dot(view.mul(-1.0)), 0.0, 1.0);
+    let limb = Vec3::new(mix(0.55, 1.0, mu), mix(0.42, 1.0, mu), mix(0.3, 1.0, mu));
 
-    let m = (lat * 9.0 + lon * 11.0).cos() * 0.5 + 0.5;
-    let mountains = mix3(cont, cont.mul(1.4), m * 0.6);
+    let c = base.mul(gran * spots * m.emission);
+    Surface {
+        emission: Vec3::new(c.x * limb.x, c.y * limb.y, c.z * limb.z),
+        ..Surface::lit(Vec3::new(0.0, 0.0, 0.0))
+    }
+}
 
-    let cap = clamp((lat.abs() - 0.6) / (1.0 - 0.6), 0.0, 1.0);
-    let ice = mix3(mountains, Vec3::new(0.9, 0.92, 0.95), cap * 0.8);
+fn shade_rocky(noise: &Noise, m: &PlanetMaterial, local: Vec3, n: Vec3, _view: Vec3, light: Vec3, _tsec: f32) -> Surface {
+    // continentes con formas retorcidas por domain warping
+    let q = noise.warp(local.mul(m.noise_scale), 0.7, 3);
+    let h = noise.fbm(q, 5, 0.5) * 0.5 + 0.5;
+    let land = smoothstep(0.35, 0.65, h);
+    let cont = mix3(m.low, m.high, land);
+
+    // cordilleras solo sobre terreno alto
+    let ridge = noise.ridged(local.mul(m.detail_scale), 4, 0.5);
+    let mountains = mix3(cont, m.accent, ridge * smoothstep(0.5, 0.7, h));
+    let cap = cap_mask(noise, m, local);
+    let albedo = polar_cap(m, cap, mountains);
+
+    // luces de ciudades: manchas finas sobre tierra firme, fuera de los
+    // casquetes y de las cordilleras, que se prenden al anochecer
+    let mut emission = Vec3::new(0.0, 0.0, 0.0);
+    if m.city_lights > 0.0 {
+        let clusters = noise.fbm(local.mul(9.0), 3, 0.5) * 0.5 + 0.5;
+        let sparks = noise.fbm(local.mul(60.0), 2, 0.6) * 0.5 + 0.5;
+        let e0 = 0.65 - m.city_lights * 0.2;
+        let cities = smoothstep(e0, e0 + 0.1, clusters) * smoothstep(0.45, 0.65, sparks);
+        let habitable = land * (1.0 - ridge * 0.7) * smoothstep(m.ice_cap_lat, m.ice_cap_lat - 0.15, local.y.abs());
+        emission = m.city_color.mul(cities * habitable * night(n, light));
+    }
 
-    let light = Vec3::new(0.6, 0.8, 0.7).norm();
-    let lambert = 0.4 + 0.6 * clamp(n.dot(light), 0.0, 1.0);
-    ice.mul(lambert)
+    // las zonas bajas son mar en la medida de `m.ocean`; el hielo también brilla
+    let gloss = (1.0 - land) * m.ocean * (1.0 - cap) + cap;
+    Surface { albedo, emission, gloss, normal: None }
 }
 
-fn shade_gas_giant(local: Vec3, n: Vec3, _view: Vec3, tsec: f32) -> Vec3 {
-    let lat = local.y;
-
-    let band_freq = 10.0;
-    let band = (band_freq * lat + tsec * 0.8).sin() * 0.5 + 0.5;
+fn shade_gas_giant(noise: &Noise, m: &PlanetMaterial, local: Vec3, _n: Vec3, _view: Vec3, _light: Vec3, tsec: f32) -> Surface {
+    // bandas por latitud, onduladas con ruido estirado a lo largo del ecuador
+    let s = m.noise_scale;
+    let flow = Vec3::new(local.x * s + tsec * 0.04, local.y * s * 3.5, local.z * s);
+    let lat = local.y + noise.fbm(flow, 4, 0.5) * 0.07;
 
-    let col1 = Vec3::new(0.9, 0.85, 0.8);
-    let col2 = Vec3::new(0.7, 0.55, 0.4);
-    let base = mix3(col1, col2, band);
+    let band = (m.bands * lat).sin() * 0.5 + 0.5;
+    let base = mix3(m.high, m.low, band);
 
-    let t_small = (lat * 25.0 + tsec * 1.5).sin() * 0.5 + 0.5;
-    let with_turb = mix3(base.mul(0.9), base.mul(1.1), t_small);
+    // remolinos finos dentro de cada banda
+    let swirl = noise.turbulence(noise.warp(flow.mul(m.detail_scale * 0.5), 0.5, 2), 4, 0.5);
+    let with_turb = mix3(base.mul(0.85), m.accent, swirl * 0.5);
 
-    let light = Vec3::new(-0.3, 0.8, 0.5).norm();
-    let lambert = 0.35 + 0.65 * clamp(n.dot(light), 0.0, 1.0);
-    with_turb.mul(lambert)
+    Surface::lit(polar_cap(m, cap_mask(noise, m, local), with_turb))
 }
 
-fn shade_ice(local: Vec3, n: Vec3, _view: Vec3, _tsec: f32) -> Vec3 {
-    let lat = local.y;
+fn shade_ice(noise: &Noise, m: &PlanetMaterial, local: Vec3, _n: Vec3, _view: Vec3, _light: Vec3, _tsec: f32) -> Surface {
+    // llanuras de hielo con tonos suaves
+    let t = noise.fbm(local.mul(m.noise_scale), 4, 0.5) * 0.5 + 0.5;
+    let ice = mix3(m.low, m.high, smoothstep(0.25, 0.7, t));
 
-    let base = Vec3::new(0.75, 0.85, 0.95);
-    let deep = Vec3::new(0.3, 0.5, 0.8);
+    // grietas: crestas finas del multifractal
+    let crack = smoothstep(0.8, 0.95, noise.ridged(local.mul(m.detail_scale), 4, 0.5));
+    let ice = mix3(ice, m.accent, crack * 0.6);
+    let cap = cap_mask(noise, m, local);
 
-    let t = (lat * 4.0).sin() * 0.5 + 0.5;
-    let ice = mix3(deep, base, t);
+    // el hielo liso refleja; las grietas no
+    Surface {
+        gloss: (1.0 - crack) * (0.6 + 0.4 * smoothstep(0.4, 0.8, t)),
+        ..Surface::lit(polar_cap(m, cap, ice))
+    }
+}
 
-    let light = Vec3::new(0.2, 0.8, 1.0).norm();
-    let lambert = 0.4 + 0.6 * clamp(n.dot(light), 0.0, 1.0);
-    ice.mul(lambert)
+/// Roca y casquetes de un volcánico: lo que no se mueve
+fn volcanic_rock(noise: &Noise, m: &PlanetMaterial, local: Vec3) -> Vec3 {
+    let rock = mix3(m.low, m.high, noise.fbm(local.mul(m.noise_scale), 4, 0.5) * 0.5 + 0.5);
+    polar_cap(m, cap_mask(noise, m, local), rock)
 }
 
-fn shade_volcanic(local: Vec3, n: Vec3, _view: Vec3, tsec: f32) -> Vec3 {
-    let lat = local.y;
-    let lon = local.z.atan2(local.x);
+/// Ríos de lava (0..1): crestas que se desplazan, más abundantes cerca del ecuador
+fn lava_mask(noise: &Noise, m: &PlanetMaterial, local: Vec3, tsec: f32) -> f32 {
+    let q = noise.warp(local.mul(m.detail_scale).add(Vec3::new(tsec * 0.05, 0.0, 0.0)), 0.4, 2);
+    let rivers = noise.ridged(q, 4, 0.5);
+    let belt = smoothstep(0.8, 0.2, local.y.abs());
+    let e0 = 1.0 - m.lava_coverage;
+    smoothstep(e0, e0 + 0.25, rivers) * (0.3 + 0.7 * belt)
+}
 
-    let rock = Vec3::new(0.12, 0.08, 0.08);
-    let lava_hot = Vec3::new(1.2, 0.5, 0.1);
+/// Lava encima de `rock`; brilla sola, de día y de noche
+fn with_lava(m: &PlanetMaterial, rock: Vec3, mask: f32) -> Surface {
+    Surface {
+        albedo: mix3(rock, m.accent, mask),
+        emission: m.accent.mul(mask * m.emission),
+        gloss: 0.0,
+        normal: None,
+    }
+}
 
-    // zonas de lava cerca del ecuador
-    let belt = (lat * 6.0).cos().max(0.0);
-    let streaks = ((lon * 8.0) + tsec * 2.0).sin() * 0.5 + 0.5;
-    let mask = (belt * streaks).powf(2.0);
+fn shade_volcanic(noise: &Noise, m: &PlanetMaterial, local: Vec3, _n: Vec3, _view: Vec3, _light: Vec3, tsec: f32) -> Surface {
+    with_lava(m, volcanic_rock(noise, m, local), lava_mask(noise, m, local, tsec))
+}
 
-    let col = mix3(rock, lava_hot, mask);
+/// Parte estática de la superficie en `local` (ver `PlanetKind::is_static`):
+/// albedo, brillo y lo que emite de noche ya a pleno; `None` en los demás modelos
+pub(crate) fn static_surface(noise: &Noise, m: &PlanetMaterial, local: Vec3) -> Option<Surface> {
+    // sin luz es de noche en todos lados: las ciudades salen enteras
+    let (dark, view) = (Vec3::new(0.0, 0.0, 0.0), local.mul(-1.0));
+    match m.kind {
+        PlanetKind::Rocky => Some(shade_rocky(noise, m, local, local, view, dark, 0.0)),
+        PlanetKind::Ice => Some(shade_ice(noise, m, local, local, view, dark, 0.0)),
+        PlanetKind::Volcanic => Some(Surface::lit(volcanic_rock(noise, m, local))),
+        _ => None,
+    }
+}
 
-    let light = Vec3::new(0.5, 0.6, 0.9).norm();
-    let lambert = 0.3 + 0.7 * clamp(n.dot(light), 0.0, 1.0);
-    col.mul(lambert)
+/// Superficie que sale de mapas, pintados (`Textured`) u horneados de un
+/// modelo estático; muestreados por latitud y longitud con el mip que
+/// corresponde a `radius_px`. `axes` son los ejes del cuerpo en cámara, para
+/// llevar el marco este/norte del mapa de normales a cámara. Lo animado del
+/// modelo (la lava) se sigue calculando encima.
+#[allow(clippy::too_many_arguments)]
+fn shade_maps(noise: &Noise, maps: &SurfaceMaps, m: &PlanetMaterial, local: Vec3, n: Vec3, light: Vec3, axes: (Vec3, Vec3, Vec3), radius_px: f32, tsec: f32) -> Surface {
+    let (u, v) = equirect_uv(local);
+    let albedo = maps.albedo.sample(u, v, maps.albedo.lod(radius_px));
+
+    let normal = maps.normal.as_ref().map(|t| {
+        let c = t.sample(u, v, t.lod(radius_px));
+        // el marco se arma en el marco local del cuerpo (derecho; la base de
+        // la cámara está espejada y ahí los productos cruz salen al revés):
+        // este = eje × punto, hacia donde crece u; justo en el polo no está
+        // definido y cualquier tangente sirve
+        let e = Vec3::new(local.z, 0.0, -local.x);
+        let east = if e.len() > 1e-4 { e.norm() } else { Vec3::new(1.0, 0.0, 0.0) };
+        let north = local.cross(east);
+        let to_cam = |v: Vec3| axes.0.mul(v.x).add(axes.1.mul(v.y)).add(axes.2.mul(v.z));
+        to_cam(east).mul(c.x * 2.0 - 1.0).add(to_cam(north).mul(c.y * 2.0 - 1.0)).add(n.mul(c.z * 2.0 - 1.0)).norm()
+    });
+    let gloss = maps.specular.as_ref().map_or(0.0, |t| {
+        let c = t.sample(u, v, t.lod(radius_px));
+        (c.x + c.y + c.z) / 3.0
+    });
+    // en uno pintado `emission` escala el mapa nocturno; en uno horneado ya
+    // viene a pleno
+    let gain = if m.kind == PlanetKind::Textured { m.emission } else { 1.0 };
+    let emission = maps.night.as_ref().map_or(Vec3::new(0.0, 0.0, 0.0), |t| {
+        t.sample(u, v, t.lod(radius_px)).mul(gain * night(n, light))
+    });
+    let surf = Surface { albedo, emission, gloss, normal };
+    if m.kind != PlanetKind::Volcanic { return surf; }
+    let lava = with_lava(m, surf.albedo, lava_mask(noise, m, local, tsec));
+    Surface { albedo: lava.albedo, emission: surf.emission.add(lava.emission), ..surf }
 }
 
-// ---------------- Render esfera desde disco 2D --------------------
+// ---------------- Nubes --------------------
+
+// cuánto se corre (en radios) la muestra de la sombra de las nubes hacia el
+// Sol; exagerado respecto de la altura real para que la sombra se note
+const CLOUD_SHADOW_OFFSET: f32 = 0.08;
+
+/// Densidad (0..1) de las nubes sobre el punto `local` de la esfera unitaria
+fn cloud_density(noise: &Noise, c: &Clouds, local: Vec3, tsec: f32) -> f32 {
+    // la capa gira sobre el eje del cuerpo, adelantada a la superficie
+    let (s, co) = (c.drift * tsec).sin_cos();
+    let p = Vec3::new(local.x * co + local.z * s, local.y, -local.x * s + local.z * co);
+    // otra región del mismo ruido, para no calcar los continentes
+    let q = noise.warp(p.mul(c.scale).add(Vec3::new(31.7, -12.3, 47.1)), 0.5, 2);
+    let v = noise.fbm(q, 5, 0.5) * 0.5 + 0.5;
+    let edge = 1.0 - c.coverage;
+    smoothstep(edge - 0.08, edge + 0.12, v)
+}
 
+// ---------------- Render esfera por intersección rayo–esfera --------------------
+
+/// Dibuja una esfera de cámara (centro `center`, radio `radius`) lanzando un
+/// rayo por píxel a través de `proj`: la silueta y la superficie salen con la
+/// perspectiva real y de muy cerca solo se ve la parte que entra en pantalla.
+/// Lo que queda delante del plano cercano se recorta.
+/// `lights` son las estrellas que lo iluminan (direcciones en espacio de
+/// cámara) y `axes` los ejes locales del cuerpo (`Body::axes`) también en
+/// cámara, para que la superficie gire con el cuerpo y no con la pantalla.
+/// `noise` es el ruido sembrado del cuerpo: dos cuerpos del mismo tipo con
+/// distinta semilla se ven distintos. `sun_visibility(k, q)` dice qué
+/// fracción de la estrella `k` llega a un punto de la superficie (relativo al
+/// centro, en cámara): sombras de anillos y eclipses.
+#[allow(clippy::too_many_arguments)]
 pub fn draw_shaded_sphere(
-    buf: &mut [u32],
-    w: usize,
-    h: usize,
-    cx: i32,
-    cy: i32,
-    radius_px: i32,
-    kind: PlanetKind,
+    fb: &mut Band,
+    proj: &Projector,
+    center: Vec3,
+    radius: f32,
+    material: &PlanetMaterial,
+    lights: &[Light],
+    axes: (Vec3, Vec3, Vec3),
+    noise: &Noise,
+    sun_visibility: &dyn Fn(usize, Vec3) -> f32,
     tsec: f32,
 ) {
-    if radius_px <= 0 { return; }
-
-    let view_dir = Vec3::new(0.0, 0.0, -1.0);
-    let r = radius_px;
-    let r2 = (r * r) as f32;
-
-    for dy in -r..=r {
-        let yy = cy + dy;
-        for dx in -r..=r {
-            let xx = cx + dx;
-
-            let fx = dx as f32;
-            let fy = dy as f32;
-            let d2 = fx * fx + fy * fy;
-            if d2 > r2 { continue; }
-
-            let nx = fx / r as f32;
-            let ny = fy / r as f32;
-            let nz = (1.0 - (nx * nx + ny * ny)).sqrt();
-            let normal = Vec3::new(nx, ny, nz).norm();
-            let local = normal;
-
-            let col = match kind {
-                PlanetKind::Star     => shade_star(local, normal, view_dir, tsec),
-                PlanetKind::Rocky    => shade_rocky(local, normal, view_dir, tsec),
-                PlanetKind::GasGiant => shade_gas_giant(local, normal, view_dir, tsec),
-                PlanetKind::Ice      => shade_ice(local, normal, view_dir, tsec),
-                PlanetKind::Volcanic => shade_volcanic(local, normal, view_dir, tsec),
+    let Some((x0, y0, x1, y1)) = proj.sphere_bounds(center, radius) else { return; };
+    let Some((y0, y1)) = fb.clip_rows(y0, y1) else { return; };
+
+    let cc = center.dot(center) - radius * radius;
+    let is_star = material.kind == PlanetKind::Star;
+    let clouds = material.clouds.as_ref().filter(|_| !is_star);
+    // dirección de cada estrella en el marco del cuerpo (para la sombra de las nubes)
+    let lights_local: Vec<Vec3> = lights
+        .iter()
+        .map(|l| Vec3::new(l.dir.dot(axes.0), l.dir.dot(axes.1), l.dir.dot(axes.2)))
+        .collect();
+    let zero = Vec3::new(0.0, 0.0, 0.0);
+    let mut reach = vec![0.0f32; lights.len()];
+    // tamaño en pantalla, para elegir el mip de las texturas (con el centro
+    // detrás de la cámara estamos encima: el mip más fino)
+    let radius_px = match proj.radius_world_to_px(radius, center.z) {
+        0 => f32::INFINITY,
+        r => r as f32,
+    };
+
+    for yy in y0..=y1 {
+        for xx in x0..=x1 {
+            let d = proj.ray_dir(xx as f32 + 0.5, yy as f32 + 0.5);
+
+            // |t·d - c|² = r²  →  a t² - 2 b t + cc = 0
+            let a = d.dot(d);
+            let b = d.dot(center);
+            let disc = b * b - a * cc;
+            if disc < 0.0 { continue; }
+
+            // primer corte; d.z = 1, así que t es la z de cámara
+            let z = (b - disc.sqrt()) / a;
+            if z < proj.z_near || z > proj.z_far { continue; }
+
+            // si ya hay algo más cerca no vale la pena sombrear
+            if !fb.depth_test(xx, yy, z) { continue; }
+
+            let hit = d.mul(z);
+            let normal = hit.sub(center).mul(1.0 / radius);
+            let local = Vec3::new(normal.dot(axes.0), normal.dot(axes.1), normal.dot(axes.2));
+            let view_dir = d.norm();
+
+            // luz de cada estrella (una estrella no se ilumina): `sky` es lo
+            // que llega arriba de las nubes y `reach` (visibilidad · sombra de
+            // nubes, por estrella) lo que llega al suelo. Los shaders reciben
+            // la suma como un solo vector de luz cuya intensidad va en el largo.
+            let mut sky = zero;
+            let (mut sky_sum, mut ground_sum) = (zero, zero);
+            reach.fill(0.0);
+            if !is_star {
+                for (k, l) in lights.iter().enumerate() {
+                    let vis = sun_visibility(k, normal.mul(radius));
+                    if vis <= 0.0 { continue; }
+                    // nubes: la sombra se toma un poco hacia la estrella, donde
+                    // está la nube que de verdad tapa este punto
+                    let shade = match clouds {
+                        Some(c) => {
+                            let above = local.add(lights_local[k].mul(CLOUD_SHADOW_OFFSET)).norm();
+                            1.0 - c.shadow * cloud_density(noise, c, above, tsec)
+                        }
+                        None => 1.0,
+                    };
+                    reach[k] = vis * shade;
+                    let ndl = clamp(normal.dot(l.dir), 0.0, 1.0);
+                    let power = (l.color.x + l.color.y + l.color.z) / 3.0;
+                    sky = sky.add(l.color.mul(ndl * vis));
+                    sky_sum = sky_sum.add(l.dir.mul(vis * power));
+                    ground_sum = ground_sum.add(l.dir.mul(vis * shade * power));
+                }
+            }
+            let cloud = clouds.map_or(0.0, |c| cloud_density(noise, c, local, tsec) * c.opacity);
+
+            let surf = match (&material.maps, material.kind) {
+                (Some(maps), _) => shade_maps(noise, maps, material, local, normal, ground_sum, axes, radius_px, tsec),
+                (None, PlanetKind::Star)     => shade_star(noise, material, local, normal, view_dir, ground_sum, tsec),
+                (None, PlanetKind::Rocky)    => shade_rocky(noise, material, local, normal, view_dir, ground_sum, tsec),
+                (None, PlanetKind::GasGiant) => shade_gas_giant(noise, material, local, normal, view_dir, ground_sum, tsec),
+                (None, PlanetKind::Ice)      => shade_ice(noise, material, local, normal, view_dir, ground_sum, tsec),
+                (None, PlanetKind::Volcanic) => shade_volcanic(noise, material, local, normal, view_dir, ground_sum, tsec),
+                (None, PlanetKind::Textured) => Surface::lit(material.low),
             };
 
+            // lo que llega al suelo con la normal del shader (la del mapa de
+            // normales si hay) y el brillo del agua/hielo
+            let shading_n = surf.normal.unwrap_or(normal);
+            let (mut ground, mut shine) = (zero, zero);
+            for (l, &r) in lights.iter().zip(reach.iter()) {
+                if r <= 0.0 { continue; }
+                ground = ground.add(l.color.mul(clamp(shading_n.dot(l.dir), 0.0, 1.0) * r));
+                if material.specular > 0.0 && surf.gloss > 0.0 {
+                    let s = specular(shading_n, view_dir, l.dir.mul(r), material.roughness);
+                    shine = shine.add(l.color.mul(s));
+                }
+            }
+
+            // luz reflejada (difusa + brillo del agua/hielo) + lo que emite la
+            // superficie; las nubes tapan todo y la aurora brilla por encima
+            let mut col = lambert(surf.albedo, ground, material.ambient);
+            if surf.gloss > 0.0 {
+                col = col.add(shine.mul(surf.gloss * material.specular));
+            }
+            let mut glow = surf.emission;
+            if let Some(c) = clouds && cloud > 0.0 {
+                col = mix3(col, lambert(c.color, sky, material.ambient), cloud);
+                glow = glow.mul(1.0 - cloud);
+            }
+            glow = glow.add(aurora(noise, material, local, normal, sky_sum, tsec));
+            let col = col.add(glow);
+
             let r8 = (clamp(col.x, 0.0, 1.0) * 255.0) as u8;
             let g8 = (clamp(col.y, 0.0, 1.0) * 255.0) as u8;
             let b8 = (clamp(col.z, 0.0, 1.0) * 255.0) as u8;
-            put_px(buf, w, h, xx, yy, rgb(r8, g8, b8));
+            put_px_z(fb, xx, yy, z, rgb(r8, g8, b8));
+            fb.emission[fb.index(xx, yy)] = glow;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image_io::Image;
+    use crate::texture::{Texture, equirect_dir};
+
+    fn flat(c: u32) -> Texture {
+        Texture::from_image(Image { w: 1, h: 1, pixels: vec![c] })
+    }
+
+    #[test]
+    fn normal_map_east_faces_east() {
+        // normal inclinada del todo hacia el este (R = 1, G = B = 0.5)
+        let maps = SurfaceMaps { albedo: flat(0xFFFFFF), normal: Some(flat(0xFF8080)), specular: None, night: None };
+        let m = PlanetMaterial::from(PlanetKind::Textured);
+        let noise = Noise::new(1);
+        let zero = Vec3::new(0.0, 0.0, 0.0);
+        // marco local tal cual y uno espejado como el de la cámara
+        let mirrored = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let identity = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        for axes in [identity, mirrored] {
+            let to_cam = |v: Vec3| axes.0.mul(v.x).add(axes.1.mul(v.y)).add(axes.2.mul(v.z));
+            for &(u, v) in &[(0.3, 0.5), (0.8, 0.3), (0.1, 0.7)] {
+                let local = equirect_dir(u, v);
+                // este = hacia donde crece u
+                let east = to_cam(equirect_dir(u + 1e-3, v).sub(local).norm());
+                let n = to_cam(local);
+                let surf = shade_maps(&noise, &maps, &m, local, n, zero, axes, 100.0, 0.0);
+                let lit = surf.normal.unwrap().dot(east);
+                assert!(lit > 0.9 && lit > n.dot(east), "u = {}, v = {}: {}", u, v, lit);
+            }
         }
     }
 }
@@ -1,7 +1,7 @@
 use crate::math::Vec3;
 use crate::camera::Camera;
 use crate::projector::Projector;
-use crate::draw::{put_px, rgb};
+use crate::draw::{Band, put_px_z, rgb};
 
 fn rng(seed: &mut u32) -> u32 {
     let mut x = *seed;
@@ -27,12 +27,10 @@ pub fn make_stars(count: usize, seed0: u32) -> Vec<Vec3> {
 }
 
 pub fn draw_stars(
-    buf: &mut [u32],
+    fb: &mut Band,
     cam: &Camera,
     proj: &Projector,
     dirs: &[Vec3],
-    w: usize,
-    h: usize,
 ) {
     let (right, up, fwd) = cam.basis();
     let far = 4000.0;
@@ -50,11 +48,13 @@ pub fn draw_stars(
             // color claro para que se vean
             let c = rgb(230, 230, 255);
             // pequeño "cross" en vez de un solo pixel
-            put_px(buf, w, h, sx, sy, c);
-            put_px(buf, w, h, sx + 1, sy, c);
-            put_px(buf, w, h, sx - 1, sy, c);
-            put_px(buf, w, h, sx, sy + 1, c);
-            put_px(buf, w, h, sx, sy - 1, c);
+            // en el plano lejano: cualquier cuerpo las tapa
+            let z = proj.z_far;
+            put_px_z(fb, sx, sy, z, c);
+            put_px_z(fb, sx + 1, sy, z, c);
+            put_px_z(fb, sx - 1, sy, z, c);
+            put_px_z(fb, sx, sy + 1, z, c);
+            put_px_z(fb, sx, sy - 1, z, c);
         }
     }
 }
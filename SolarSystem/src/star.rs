@@ -0,0 +1,125 @@
+use crate::draw::{Band, rgb};
+use crate::math::Vec3;
+use crate::noise::Noise;
+use crate::projector::Projector;
+use crate::shading::smoothstep;
+
+/// Color (0..1, el canal más fuerte en 1) de un cuerpo negro a `kelvin`.
+/// Aproximación de Tanner Helland a la curva de Planck vista por el ojo;
+/// vale entre ~1000 K (rojo) y ~40000 K (azul).
+pub fn blackbody(kelvin: f32) -> Vec3 {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+    let r = if t <= 66.0 { 255.0 } else { 329.7 * (t - 60.0).powf(-0.1332) };
+    let g = if t <= 66.0 {
+        99.47 * t.ln() - 161.12
+    } else {
+        288.12 * (t - 60.0).powf(-0.0755)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.52 * (t - 10.0).ln() - 305.04
+    };
+    let ch = |v: f32| v.clamp(0.0, 255.0) / 255.0;
+    Vec3::new(ch(r), ch(g), ch(b))
+}
+
+/// Temperatura de una estrella a la que no se le da otra (K)
+pub const SUN_TEMPERATURE: f32 = 5778.0;
+
+/// Color de la luz de una estrella con el blanco puesto en el Sol: una de
+/// 5778 K ilumina blanco, una más fría tiñe de naranja y una más caliente de azul
+pub fn light_color(kelvin: f32) -> Vec3 {
+    let c = blackbody(kelvin);
+    let w = blackbody(SUN_TEMPERATURE);
+    Vec3::new(c.x / w.x, c.y / w.y, c.z / w.z)
+}
+
+/// Luminosidad relativa (Stefan–Boltzmann, L ∝ R² T⁴) con el Sol como unidad
+/// de temperatura; solo sirve para comparar estrellas entre sí
+pub fn luminosity(radius: f32, kelvin: f32) -> f32 {
+    let t = kelvin / SUN_TEMPERATURE;
+    radius * radius * t * t * t * t
+}
+
+/// Luz de una estrella tal como le llega a un cuerpo
+#[derive(Clone, Copy, Debug)]
+pub struct Light {
+    /// Dirección cuerpo → estrella (unitaria)
+    pub dir: Vec3,
+    /// Color de la estrella por la parte de la luz total que aporta
+    pub color: Vec3,
+}
+
+// hasta dónde llega el halo, en radios de la estrella
+pub const CORONA_EXTENT: f32 = 3.0;
+
+/// Corona, halo y protuberancias alrededor del disco de una estrella de
+/// cámara (`center`, `radius`). Se suma a lo que hay (es luz) y no escribe
+/// profundidad; lo que esté más cerca que la estrella lo tapa.
+/// `color` es el de cuerpo negro ya escalado por el brillo, `prominence` el
+/// de las protuberancias y `axes` los ejes locales de la estrella en cámara,
+/// así las estructuras giran con ella.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_corona(
+    fb: &mut Band,
+    proj: &Projector,
+    center: Vec3,
+    radius: f32,
+    color: Vec3,
+    prominence: Vec3,
+    axes: (Vec3, Vec3, Vec3),
+    noise: &Noise,
+    tsec: f32,
+) {
+    let Some((x0, y0, x1, y1)) = proj.sphere_bounds(center, radius * CORONA_EXTENT) else { return; };
+    let Some((y0, y1)) = fb.clip_rows(y0, y1) else { return; };
+
+    for yy in y0..=y1 {
+        for xx in x0..=x1 {
+            let d = proj.ray_dir(xx as f32 + 0.5, yy as f32 + 0.5);
+            // punto del rayo más cercano al centro: ahí se mide la altura
+            let tc = d.dot(center) / d.dot(d);
+            if tc < proj.z_near { continue; }
+            let off = d.mul(tc).sub(center);
+            let b = off.len() / radius;
+            if b <= 1.0 || b >= CORONA_EXTENT { continue; }
+            let i = fb.index(xx, yy);
+            if fb.depth[i] < tc { continue; }
+
+            let dir = off.mul(1.0 / (b * radius));
+            let local = Vec3::new(dir.dot(axes.0), dir.dot(axes.1), dir.dot(axes.2));
+
+            // halo suave + corona en rayos, más larga sobre el ecuador
+            let h = b - 1.0;
+            let halo = 0.55 * (-h * 4.0).exp() + 0.08 / (b * b);
+            let streamers = 0.55 + 0.45 * noise.fbm(local.mul(3.0).add(Vec3::new(0.0, tsec * 0.02, 0.0)), 3, 0.5);
+            let equator = 0.5 + 0.5 * (1.0 - local.y.abs());
+            let corona = 0.35 * streamers * equator * (-h * 1.6).exp();
+            let glow = (halo + corona) * smoothstep(CORONA_EXTENT, CORONA_EXTENT * 0.7, b);
+
+            // protuberancias: filamentos en arco pegados al limbo, solo en
+            // algunas zonas activas que van cambiando
+            let mut prom = 0.0;
+            if h < 0.35 {
+                let active = smoothstep(0.1, 0.35, noise.fbm(local.mul(1.3).add(Vec3::new(tsec * 0.01, 0.0, 0.0)), 2, 0.5));
+                if active > 0.0 {
+                    let q = local.mul(b * 7.0).add(Vec3::new(0.0, tsec * 0.04, tsec * 0.03));
+                    let arcs = smoothstep(0.7, 0.9, noise.ridged(q, 3, 0.5));
+                    let top = 0.1 + 0.22 * (noise.perlin(local.mul(4.0)) * 0.5 + 0.5);
+                    prom = arcs * active * smoothstep(top, top * 0.5, h);
+                }
+            }
+
+            let add = color.mul(glow).add(prominence.mul(prom));
+            let dst = fb.color[i];
+            let ch = |sh: u32, v: f32| ((((dst >> sh) & 0xFF) as f32 / 255.0 + v).clamp(0.0, 1.0) * 255.0) as u8;
+            fb.color[i] = rgb(ch(16, add.x), ch(8, add.y), ch(0, add.z));
+            if prom > 0.0 {
+                fb.emission[i] = fb.emission[i].add(prominence.mul(prom));
+            }
+        }
+    }
+}
@@ -0,0 +1,751 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::bodies::body::Body;
+use crate::atmosphere::Atmosphere;
+use crate::bodies::orbit::Orbit;
+use crate::bodies::rings::Rings;
+use crate::material::{Clouds, PlanetMaterial};
+use crate::math::Vec3;
+use crate::nbody::NBodyParams;
+use crate::noise::seed_from_str;
+use crate::scene::Scene;
+use crate::shading::PlanetKind;
+use crate::star::SUN_TEMPERATURE;
+use crate::texture::{SurfaceMaps, Texture};
+
+/// El sistema que trae el binario (Sol + 4 planetas)
+pub const DEFAULT_SYSTEM: &str = include_str!("../systems/default.toml");
+
+// ---------------- Formato del archivo (TOML) --------------------
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SystemDef {
+    #[serde(default)]
+    skybox: SkyboxDef,
+    #[serde(default)]
+    nbody: NBodyDef,
+    #[serde(default, rename = "body")]
+    bodies: Vec<BodyDef>,
+}
+
+/// Parámetros del modo N-cuerpos; lo que falte toma el valor por defecto
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct NBodyDef {
+    g: Option<f32>,
+    sub_step: Option<f32>,
+    softening: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SkyboxDef {
+    stars: usize,
+    seed: u32,
+}
+
+impl Default for SkyboxDef {
+    fn default() -> Self {
+        Self { stars: 1200, seed: 0xC0FFEE }
+    }
+}
+
+/// Un cuerpo del archivo. Las lunas usan la misma forma ([[body.moon]],
+/// [[body.moon.moon]], ...); para ellas `kind` es opcional (rocky).
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BodyDef {
+    name: String,
+    kind: Option<PlanetKind>,
+    radius: f32,
+    /// masa para el modo N-cuerpos; por defecto radius³ (densidad 1)
+    mass: Option<f32>,
+    // elementos orbitales (ángulos en radianes); los alias son los nombres viejos
+    #[serde(default, alias = "orbit_radius")]
+    semi_major_axis: f32,
+    #[serde(default)]
+    eccentricity: f32,
+    #[serde(default)]
+    inclination: f32,
+    #[serde(default)]
+    ascending_node: f32,
+    #[serde(default)]
+    arg_periapsis: f32,
+    #[serde(default, alias = "phase")]
+    mean_anomaly: f32,
+    #[serde(default, alias = "orbit_speed")]
+    mean_motion: f32,
+    #[serde(default)]
+    rot_speed: f32,
+    #[serde(default)]
+    axial_tilt: f32,
+    /// color plano del modo rápido; por defecto sale del material
+    color: Option<String>,
+    /// semilla del ruido; por defecto sale del nombre
+    seed: Option<u32>,
+    /// temperatura de una estrella en K; por defecto la del Sol
+    temperature: Option<f32>,
+    draw_orbit: Option<bool>,
+    /// ajustes sobre el material por defecto de `kind`
+    material: Option<MaterialDef>,
+    atmosphere: Option<AtmosphereDef>,
+    clouds: Option<CloudsDef>,
+    rings: Option<RingsDef>,
+    #[serde(default, rename = "moon")]
+    moons: Vec<BodyDef>,
+}
+
+/// [body.material]: cualquier campo que falte queda como en el preset
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialDef {
+    low: Option<String>,
+    high: Option<String>,
+    accent: Option<String>,
+    cap_color: Option<String>,
+    noise_scale: Option<f32>,
+    detail_scale: Option<f32>,
+    bands: Option<f32>,
+    ice_cap_lat: Option<f32>,
+    lava_coverage: Option<f32>,
+    emission: Option<f32>,
+    city_lights: Option<f32>,
+    city_color: Option<String>,
+    aurora: Option<f32>,
+    aurora_color: Option<String>,
+    ambient: Option<f32>,
+    specular: Option<f32>,
+    roughness: Option<f32>,
+    ocean: Option<f32>,
+    sunspots: Option<f32>,
+    // mapas equirectangulares (PPM, PNG o TGA) de kind = "textured", con la
+    // ruta relativa al archivo del sistema
+    albedo_map: Option<String>,
+    normal_map: Option<String>,
+    specular_map: Option<String>,
+    night_map: Option<String>,
+}
+
+/// [body.atmosphere]: su sola presencia le da aire al cuerpo
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AtmosphereDef {
+    height: Option<f32>,
+    density: Option<f32>,
+    rayleigh: Option<String>,
+    mie: Option<String>,
+}
+
+/// [body.clouds]: su sola presencia le da nubes al cuerpo
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CloudsDef {
+    coverage: Option<f32>,
+    drift: Option<f32>,
+    scale: Option<f32>,
+    color: Option<String>,
+    opacity: Option<f32>,
+    shadow: Option<f32>,
+}
+
+/// [body.rings]: radios en unidades de mundo desde el centro del cuerpo
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RingsDef {
+    inner: f32,
+    outer: f32,
+    #[serde(default)]
+    tilt: f32,
+    color_inner: Option<String>,
+    color_outer: Option<String>,
+    opacity: Option<f32>,
+    /// pares [posición 0..1, densidad 0..1]
+    #[serde(default)]
+    profile: Vec<[f32; 2]>,
+}
+
+// ---------------- Errores --------------------
+
+#[derive(Debug)]
+pub enum SystemError {
+    Io(io::Error),
+    /// TOML mal formado o campo con tipo incorrecto (incluye línea/columna)
+    Parse(toml::de::Error),
+    /// El archivo es válido pero un cuerpo no tiene sentido
+    Invalid { entry: String, msg: String },
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemError::Io(e) => write!(f, "no se pudo leer el sistema: {}", e),
+            SystemError::Parse(e) => write!(f, "sistema mal formado: {}", e),
+            SystemError::Invalid { entry, msg } => write!(f, "{}: {}", entry, msg),
+        }
+    }
+}
+
+impl std::error::Error for SystemError {}
+
+fn invalid(entry: &str, msg: impl Into<String>) -> SystemError {
+    SystemError::Invalid { entry: entry.to_string(), msg: msg.into() }
+}
+
+// ---------------- Carga --------------------
+
+fn parse_color(entry: &str, s: &str) -> Result<u32, SystemError> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 {
+        return Err(invalid(entry, format!("color '{}' no tiene forma #RRGGBB", s)));
+    }
+    u32::from_str_radix(hex, 16)
+        .map_err(|_| invalid(entry, format!("color '{}' no es hexadecimal", s)))
+}
+
+fn parse_color_vec(entry: &str, s: &str) -> Result<Vec3, SystemError> {
+    let c = parse_color(entry, s)?;
+    let ch = |sh: u32| ((c >> sh) & 0xFF) as f32 / 255.0;
+    Ok(Vec3::new(ch(16), ch(8), ch(0)))
+}
+
+/// Aplica los campos de [body.material] sobre el preset y los valida; los
+/// mapas se leen desde `base`
+fn build_material(entry: &str, kind: PlanetKind, def: Option<MaterialDef>, base: &Path) -> Result<PlanetMaterial, SystemError> {
+    let mut m = PlanetMaterial::preset(kind);
+    let Some(d) = def else {
+        if kind == PlanetKind::Textured {
+            return Err(invalid(entry, "kind = \"textured\" necesita material.albedo_map"));
+        }
+        return Ok(m);
+    };
+
+    for (slot, value) in [
+        (&mut m.low, &d.low),
+        (&mut m.high, &d.high),
+        (&mut m.accent, &d.accent),
+        (&mut m.cap_color, &d.cap_color),
+        (&mut m.city_color, &d.city_color),
+        (&mut m.aurora_color, &d.aurora_color),
+    ] {
+        if let Some(c) = value {
+            *slot = parse_color_vec(entry, c)?;
+        }
+    }
+    for (slot, value) in [
+        (&mut m.noise_scale, d.noise_scale),
+        (&mut m.detail_scale, d.detail_scale),
+        (&mut m.bands, d.bands),
+        (&mut m.ice_cap_lat, d.ice_cap_lat),
+        (&mut m.lava_coverage, d.lava_coverage),
+        (&mut m.emission, d.emission),
+        (&mut m.city_lights, d.city_lights),
+        (&mut m.aurora, d.aurora),
+        (&mut m.ambient, d.ambient),
+        (&mut m.specular, d.specular),
+        (&mut m.roughness, d.roughness),
+        (&mut m.ocean, d.ocean),
+        (&mut m.sunspots, d.sunspots),
+    ] {
+        if let Some(v) = value {
+            *slot = v;
+        }
+    }
+
+    check_finite(entry, &[
+        ("material.noise_scale", m.noise_scale),
+        ("material.detail_scale", m.detail_scale),
+        ("material.bands", m.bands),
+        ("material.ice_cap_lat", m.ice_cap_lat),
+        ("material.lava_coverage", m.lava_coverage),
+        ("material.emission", m.emission),
+        ("material.city_lights", m.city_lights),
+        ("material.aurora", m.aurora),
+        ("material.ambient", m.ambient),
+        ("material.specular", m.specular),
+        ("material.roughness", m.roughness),
+        ("material.ocean", m.ocean),
+        ("material.sunspots", m.sunspots),
+    ])?;
+    if m.noise_scale <= 0.0 || m.detail_scale <= 0.0 {
+        return Err(invalid(entry, "material: noise_scale y detail_scale tienen que ser > 0"));
+    }
+    if m.bands < 0.0 || m.emission < 0.0 || m.aurora < 0.0 || m.specular < 0.0 {
+        return Err(invalid(entry, "material: bands, emission, aurora y specular no pueden ser negativos"));
+    }
+    if m.roughness <= 0.0 || m.roughness > 1.0 {
+        return Err(invalid(entry, "material.roughness tiene que estar en (0, 1]"));
+    }
+    for (name, v) in [
+        ("ice_cap_lat", m.ice_cap_lat),
+        ("lava_coverage", m.lava_coverage),
+        ("city_lights", m.city_lights),
+        ("ambient", m.ambient),
+        ("ocean", m.ocean),
+        ("sunspots", m.sunspots),
+    ] {
+        if !(0.0..=1.0).contains(&v) {
+            return Err(invalid(entry, format!("material.{} tiene que estar en [0, 1]", name)));
+        }
+    }
+
+    let has_maps = d.albedo_map.is_some() || d.normal_map.is_some() || d.specular_map.is_some() || d.night_map.is_some();
+    if kind != PlanetKind::Textured {
+        if has_maps {
+            return Err(invalid(entry, "los mapas de material solo valen con kind = \"textured\""));
+        }
+        return Ok(m);
+    }
+    let load = |name: &str, file: &Option<String>| -> Result<Option<Texture>, SystemError> {
+        let Some(file) = file else { return Ok(None); };
+        Texture::load(&base.join(file))
+            .map(Some)
+            .map_err(|e| invalid(entry, format!("material.{} '{}': {}", name, file, e)))
+    };
+    let Some(albedo) = load("albedo_map", &d.albedo_map)? else {
+        return Err(invalid(entry, "kind = \"textured\" necesita material.albedo_map"));
+    };
+    m.maps = Some(Arc::new(SurfaceMaps {
+        albedo,
+        normal: load("normal_map", &d.normal_map)?,
+        specular: load("specular_map", &d.specular_map)?,
+        night: load("night_map", &d.night_map)?,
+    }));
+    Ok(m)
+}
+
+fn build_atmosphere(entry: &str, d: AtmosphereDef) -> Result<Atmosphere, SystemError> {
+    let mut a = Atmosphere::default();
+    if let Some(h) = d.height { a.height = h; }
+    if let Some(rho) = d.density { a.density = rho; }
+    if let Some(c) = &d.rayleigh { a.rayleigh = parse_color_vec(entry, c)?; }
+    if let Some(c) = &d.mie { a.mie = parse_color_vec(entry, c)?; }
+    check_finite(entry, &[("atmosphere.height", a.height), ("atmosphere.density", a.density)])?;
+    if a.height <= 0.0 {
+        return Err(invalid(entry, "atmosphere.height tiene que ser > 0"));
+    }
+    if a.density < 0.0 {
+        return Err(invalid(entry, "atmosphere.density no puede ser negativa"));
+    }
+    Ok(a)
+}
+
+fn build_clouds(entry: &str, d: CloudsDef) -> Result<Clouds, SystemError> {
+    let mut c = Clouds::default();
+    if let Some(v) = d.coverage { c.coverage = v; }
+    if let Some(v) = d.drift { c.drift = v; }
+    if let Some(v) = d.scale { c.scale = v; }
+    if let Some(v) = &d.color { c.color = parse_color_vec(entry, v)?; }
+    if let Some(v) = d.opacity { c.opacity = v; }
+    if let Some(v) = d.shadow { c.shadow = v; }
+    check_finite(entry, &[("clouds.drift", c.drift), ("clouds.scale", c.scale)])?;
+    if c.scale <= 0.0 {
+        return Err(invalid(entry, "clouds.scale tiene que ser > 0"));
+    }
+    for (name, v) in [("coverage", c.coverage), ("opacity", c.opacity), ("shadow", c.shadow)] {
+        if !(0.0..=1.0).contains(&v) {
+            return Err(invalid(entry, format!("clouds.{} tiene que estar en [0, 1]", name)));
+        }
+    }
+    Ok(c)
+}
+
+fn build_rings(entry: &str, radius: f32, d: RingsDef) -> Result<Rings, SystemError> {
+    let default_color = Vec3::new(0.78, 0.71, 0.59);
+    let color_inner = match &d.color_inner {
+        Some(c) => parse_color_vec(entry, c)?,
+        None => default_color,
+    };
+    let color_outer = match &d.color_outer {
+        Some(c) => parse_color_vec(entry, c)?,
+        None => color_inner,
+    };
+    let rings = Rings {
+        inner: d.inner,
+        outer: d.outer,
+        tilt: d.tilt,
+        color_inner,
+        color_outer,
+        opacity: d.opacity.unwrap_or(0.8),
+        profile: d.profile.iter().map(|p| (p[0], p[1])).collect(),
+    };
+
+    check_finite(entry, &[
+        ("rings.inner", rings.inner),
+        ("rings.outer", rings.outer),
+        ("rings.tilt", rings.tilt),
+        ("rings.opacity", rings.opacity),
+    ])?;
+    if rings.inner <= radius || rings.outer <= rings.inner {
+        return Err(invalid(entry, "rings: tiene que cumplirse radius < inner < outer"));
+    }
+    if !(0.0..=1.0).contains(&rings.opacity) {
+        return Err(invalid(entry, "rings.opacity tiene que estar en [0, 1]"));
+    }
+    let mut last = 0.0;
+    for &(u, dens) in &rings.profile {
+        if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&dens) {
+            return Err(invalid(entry, "rings.profile: cada par [posición, densidad] va en [0, 1]"));
+        }
+        if u < last {
+            return Err(invalid(entry, "rings.profile: las posiciones tienen que ir en orden"));
+        }
+        last = u;
+    }
+    Ok(rings)
+}
+
+fn check_finite(entry: &str, fields: &[(&str, f32)]) -> Result<(), SystemError> {
+    for (name, v) in fields {
+        if !v.is_finite() {
+            return Err(invalid(entry, format!("{} no es un número finito", name)));
+        }
+    }
+    Ok(())
+}
+
+/// Lee un sistema desde un archivo TOML; los mapas de textura se buscan
+/// junto al archivo
+pub fn load_system(path: &Path) -> Result<Scene, SystemError> {
+    let src = fs::read_to_string(path).map_err(SystemError::Io)?;
+    parse_system_in(&src, path.parent().unwrap_or(Path::new("")))
+}
+
+/// Arma una escena desde el texto TOML de un sistema (mapas relativos al
+/// directorio actual)
+pub fn parse_system(src: &str) -> Result<Scene, SystemError> {
+    parse_system_in(src, Path::new(""))
+}
+
+/// Como `parse_system`, con las rutas de los mapas relativas a `base`
+pub fn parse_system_in(src: &str, base: &Path) -> Result<Scene, SystemError> {
+    let def: SystemDef = toml::from_str(src).map_err(SystemError::Parse)?;
+
+    if def.bodies.is_empty() {
+        return Err(invalid("sistema", "no tiene ningún [[body]]"));
+    }
+
+    let mut scene = Scene::new(def.skybox.stars, def.skybox.seed);
+    let mut names = HashSet::new();
+
+    let defaults = NBodyParams::default();
+    scene.nbody = NBodyParams {
+        g: def.nbody.g.unwrap_or(defaults.g),
+        sub_step: def.nbody.sub_step.unwrap_or(defaults.sub_step),
+        softening: def.nbody.softening.unwrap_or(defaults.softening),
+    };
+    let p = scene.nbody;
+    if !(p.g.is_finite() && p.g > 0.0) {
+        return Err(invalid("[nbody]", "g tiene que ser > 0"));
+    }
+    if !(p.sub_step.is_finite() && p.sub_step > 0.0) {
+        return Err(invalid("[nbody]", "sub_step tiene que ser > 0"));
+    }
+    if !(p.softening.is_finite() && p.softening >= 0.0) {
+        return Err(invalid("[nbody]", "softening no puede ser negativo"));
+    }
+
+    for (i, b) in def.bodies.into_iter().enumerate() {
+        let entry = format!("body #{} (\"{}\")", i + 1, b.name);
+        add_body(&mut scene, &mut names, entry, None, b, base)?;
+    }
+    check_stars(&scene)?;
+
+    Ok(scene)
+}
+
+/// Tiene que haber al menos una estrella, y si varias giran alrededor del
+/// origen tienen que equilibrarse ahí (el origen es su baricentro)
+fn check_stars(scene: &Scene) -> Result<(), SystemError> {
+    let stars = scene.stars();
+    if stars.is_empty() {
+        return Err(invalid("sistema", "no tiene ninguna estrella (kind = \"star\")"));
+    }
+    let roots: Vec<usize> = stars.into_iter().filter(|&i| scene.bodies[i].body.parent.is_none()).collect();
+    let reach = roots.iter().map(|&i| scene.bodies[i].body.orbit.semi_major).fold(0.0, f32::max);
+    if roots.len() < 2 || reach <= 0.0 {
+        return Ok(());
+    }
+    // momento de masa de las estrellas respecto al origen, en dos instantes
+    for t in [0.0, 1.0] {
+        let (mut moment, mut mass) = (Vec3::new(0.0, 0.0, 0.0), 0.0);
+        for &i in &roots {
+            let b = &scene.bodies[i].body;
+            moment = moment.add(b.orbit.offset(t).mul(b.mass));
+            mass += b.mass;
+        }
+        if moment.len() / mass > 0.02 * reach {
+            return Err(invalid(
+                "sistema",
+                "las estrellas no giran alrededor de un baricentro en el origen: para una binaria \
+                 van con el mismo mean_motion, mean_anomaly separadas por π y \
+                 semi_major_axis en proporción inversa a las masas",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Valida y agrega un cuerpo bajo `parent`, y después sus lunas (recursivo)
+fn add_body(
+    scene: &mut Scene,
+    names: &mut HashSet<String>,
+    entry: String,
+    parent: Option<usize>,
+    b: BodyDef,
+    base: &Path,
+) -> Result<(), SystemError> {
+    if !names.insert(b.name.clone()) {
+        return Err(invalid(&entry, "nombre repetido"));
+    }
+    check_finite(&entry, &[
+        ("radius", b.radius),
+        ("semi_major_axis", b.semi_major_axis),
+        ("eccentricity", b.eccentricity),
+        ("inclination", b.inclination),
+        ("ascending_node", b.ascending_node),
+        ("arg_periapsis", b.arg_periapsis),
+        ("mean_anomaly", b.mean_anomaly),
+        ("mean_motion", b.mean_motion),
+        ("rot_speed", b.rot_speed),
+        ("axial_tilt", b.axial_tilt),
+    ])?;
+
+    let kind = match (b.kind, parent) {
+        (Some(k), _) => k,
+        (None, Some(_)) => PlanetKind::Rocky,
+        (None, None) => return Err(invalid(&entry, "falta kind")),
+    };
+    if parent.is_some() && kind == PlanetKind::Star {
+        return Err(invalid(&entry, "una luna no puede ser estrella"));
+    }
+    if b.radius <= 0.0 {
+        return Err(invalid(&entry, "radius tiene que ser > 0"));
+    }
+    let mass = b.mass.unwrap_or(b.radius * b.radius * b.radius);
+    if !(mass.is_finite() && mass > 0.0) {
+        return Err(invalid(&entry, "mass tiene que ser > 0"));
+    }
+    if b.semi_major_axis < 0.0 {
+        return Err(invalid(&entry, "semi_major_axis no puede ser negativo"));
+    }
+    if !(0.0..1.0).contains(&b.eccentricity) {
+        return Err(invalid(&entry, "eccentricity tiene que estar en [0, 1)"));
+    }
+
+    let orbit = Orbit {
+        semi_major: b.semi_major_axis,
+        eccentricity: b.eccentricity,
+        inclination: b.inclination,
+        lon_asc_node: b.ascending_node,
+        arg_periapsis: b.arg_periapsis,
+        mean_anomaly: b.mean_anomaly,
+        mean_motion: b.mean_motion,
+    };
+
+    // el periapsis no puede meter al cuerpo dentro de su padre
+    match parent {
+        Some(p) => {
+            let parent_radius = scene.bodies[p].body.radius;
+            if orbit.periapsis() <= parent_radius + b.radius {
+                return Err(invalid(&entry, "el periapsis no alcanza para quedar fuera del padre"));
+            }
+        }
+        // una estrella de una binaria puede envolver al baricentro
+        None if kind == PlanetKind::Star => {}
+        None => {
+            if orbit.semi_major > 0.0 && orbit.periapsis() <= b.radius {
+                return Err(invalid(&entry, "el periapsis tiene que ser mayor que radius"));
+            }
+        }
+    }
+
+    let color = match &b.color {
+        Some(c) => Some(parse_color(&entry, c)?),
+        None => None,
+    };
+
+    let mut material = build_material(&entry, kind, b.material, base)?;
+    if let Some(a) = b.atmosphere {
+        if kind == PlanetKind::Star {
+            return Err(invalid(&entry, "una estrella no lleva [atmosphere]"));
+        }
+        material.atmosphere = Some(build_atmosphere(&entry, a)?);
+    }
+    if let Some(c) = b.clouds {
+        if kind == PlanetKind::Star {
+            return Err(invalid(&entry, "una estrella no lleva [clouds]"));
+        }
+        material.clouds = Some(build_clouds(&entry, c)?);
+    }
+    let rings = match b.rings {
+        Some(r) => Some(build_rings(&entry, b.radius, r)?),
+        None => None,
+    };
+    let seed = b.seed.unwrap_or_else(|| seed_from_str(&b.name));
+    let temperature = match b.temperature {
+        Some(_) if kind != PlanetKind::Star => {
+            return Err(invalid(&entry, "temperature solo vale para estrellas"));
+        }
+        Some(k) if !(1000.0..=40000.0).contains(&k) => {
+            return Err(invalid(&entry, "temperature tiene que estar entre 1000 y 40000 K"));
+        }
+        Some(k) => k,
+        None => SUN_TEMPERATURE,
+    };
+
+    let idx = scene.add(
+        Body {
+            name: b.name,
+            radius: b.radius,
+            mass,
+            orbit,
+            rot_speed: b.rot_speed,
+            axial_tilt: b.axial_tilt,
+            color,
+            seed,
+            temperature,
+            draw_orbit: b.draw_orbit.unwrap_or(orbit.semi_major > 0.0),
+            rings,
+            parent,
+        },
+        material,
+    );
+
+    for (j, m) in b.moons.into_iter().enumerate() {
+        let moon_entry = format!("{} moon #{} (\"{}\")", entry, j + 1, m.name);
+        add_body(scene, names, moon_entry, Some(idx), m, base)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAR: &str = "[[body]]\nname = \"Sol\"\nkind = \"star\"\nradius = 5.0\n";
+    const PLANET: &str = "[[body]]\nname = \"Roca\"\nkind = \"rocky\"\nradius = 1.0\n\
+                          semi_major_axis = 20.0\nmean_motion = 0.1\n";
+
+    /// Sol + un planeta, con `extra` agregado al final del planeta
+    fn system(extra: &str) -> String {
+        format!("{}\n{}{}\n", STAR, PLANET, extra)
+    }
+
+    fn invalid_msg(src: &str) -> String {
+        match parse_system(src) {
+            Err(SystemError::Invalid { msg, .. }) => msg,
+            Err(e) => panic!("error inesperado: {}\n{}", e, src),
+            Ok(_) => panic!("se aceptó:\n{}", src),
+        }
+    }
+
+    #[test]
+    fn accepts_a_full_system() {
+        let src = system(
+            "color = \"#336699\"\nseed = 42\n\
+             [body.material]\nlow = \"#102030\"\nocean = 0.5\n\
+             [body.atmosphere]\nheight = 0.1\n\
+             [body.clouds]\ncoverage = 0.4\n\
+             [body.rings]\ninner = 1.5\nouter = 2.5\nprofile = [[0.0, 0.2], [0.5, 1.0], [1.0, 0.3]]\n\
+             [[body.moon]]\nname = \"Luna\"\nradius = 0.2\nsemi_major_axis = 4.0\n",
+        );
+        let scene = parse_system(&src).unwrap();
+        assert_eq!(scene.bodies.len(), 3);
+        let (sol, roca, luna) = (&scene.bodies[0], &scene.bodies[1], &scene.bodies[2]);
+        assert_eq!(sol.material.kind, PlanetKind::Star);
+        assert_eq!(sol.body.color, None);
+        assert_eq!(roca.body.color, Some(0x336699));
+        assert_eq!(roca.body.seed, 42);
+        assert!(roca.body.draw_orbit);
+        assert!((roca.material.low.y - 0x20 as f32 / 255.0).abs() < 1e-6 && roca.material.ocean == 0.5);
+        assert!(roca.material.atmosphere.is_some() && roca.material.clouds.is_some());
+        assert_eq!(roca.body.rings.as_ref().unwrap().profile.len(), 3);
+        assert_eq!((luna.body.parent, luna.material.kind), (Some(1), PlanetKind::Rocky));
+
+        // los que trae el repo, con los nombres viejos de los campos incluidos
+        for src in [DEFAULT_SYSTEM, include_str!("../systems/binary.toml")] {
+            parse_system(src).unwrap();
+        }
+        let old = system("").replace("semi_major_axis", "orbit_radius").replace("mean_motion", "orbit_speed");
+        assert_eq!(parse_system(&old).unwrap().bodies[1].body.orbit.semi_major, 20.0);
+    }
+
+    #[test]
+    fn rejects_invalid_systems() {
+        let moon = |extra: &str| system(&format!("[[body.moon]]\nname = \"Luna\"\nradius = 0.2\n{}", extra));
+        let cases = [
+            // sistema
+            (String::new(), "ningún [[body]]"),
+            (PLANET.to_string(), "ninguna estrella"),
+            (format!("[nbody]\ng = 0.0\n{}", system("")), "g tiene que ser > 0"),
+            (format!("[nbody]\nsub_step = -1.0\n{}", system("")), "sub_step"),
+            (format!("[nbody]\nsoftening = -1.0\n{}", system("")), "softening"),
+            (
+                format!(
+                    "{}semi_major_axis = 10.0\nmean_motion = 0.1\n\n{}",
+                    STAR,
+                    STAR.replace("Sol", "Sol B") + "semi_major_axis = 10.0\nmean_motion = 0.1\n"
+                ),
+                "baricentro",
+            ),
+            // cuerpo
+            (format!("{}\n{}", system(""), PLANET), "nombre repetido"),
+            (system("axial_tilt = nan"), "axial_tilt no es un número finito"),
+            (system("").replace("kind = \"rocky\"\n", ""), "falta kind"),
+            (moon("kind = \"star\"\nsemi_major_axis = 4.0"), "una luna no puede ser estrella"),
+            (system("").replace("radius = 1.0", "radius = 0.0"), "radius tiene que ser > 0"),
+            (system("mass = -1.0"), "mass tiene que ser > 0"),
+            (system("").replace("semi_major_axis = 20.0", "semi_major_axis = -20.0"), "semi_major_axis"),
+            (system("eccentricity = 1.0"), "eccentricity"),
+            (system("eccentricity = 0.96"), "periapsis tiene que ser mayor que radius"),
+            (moon("semi_major_axis = 1.1"), "fuera del padre"),
+            (system("color = \"#12345\""), "#RRGGBB"),
+            (system("color = \"#12345G\""), "hexadecimal"),
+            (system("temperature = 3000.0"), "temperature solo vale para estrellas"),
+            (format!("{}temperature = 500.0\n\n{}", STAR, PLANET), "entre 1000 y 40000 K"),
+            (format!("{}[body.atmosphere]\n\n{}", STAR, PLANET), "[atmosphere]"),
+            (format!("{}[body.clouds]\n\n{}", STAR, PLANET), "[clouds]"),
+            // material
+            (system("[body.material]\nnoise_scale = 0.0"), "noise_scale y detail_scale"),
+            (system("[body.material]\nemission = -1.0"), "no pueden ser negativos"),
+            (system("[body.material]\nroughness = 0.0"), "roughness"),
+            (system("[body.material]\nocean = 1.5"), "material.ocean"),
+            (system("[body.material]\nalbedo_map = \"a.png\""), "solo valen con kind = \"textured\""),
+            (system("").replace("rocky", "textured"), "necesita material.albedo_map"),
+            (system("[body.material]\nlow = \"#000000\"").replace("rocky", "textured"), "necesita material.albedo_map"),
+            (
+                system("[body.material]\nalbedo_map = \"no/existe.png\"").replace("rocky", "textured"),
+                "material.albedo_map 'no/existe.png'",
+            ),
+            // aire y nubes
+            (system("[body.atmosphere]\nheight = 0.0"), "atmosphere.height"),
+            (system("[body.atmosphere]\ndensity = -1.0"), "atmosphere.density"),
+            (system("[body.clouds]\nscale = 0.0"), "clouds.scale"),
+            (system("[body.clouds]\ncoverage = 2.0"), "clouds.coverage"),
+            // anillos
+            (system("[body.rings]\ninner = 0.5\nouter = 2.0"), "radius < inner < outer"),
+            (system("[body.rings]\ninner = 2.0\nouter = 1.5"), "radius < inner < outer"),
+            (system("[body.rings]\ninner = 1.5\nouter = 2.0\nopacity = 2.0"), "rings.opacity"),
+            (system("[body.rings]\ninner = 1.5\nouter = 2.0\nprofile = [[0.5, 1.5]]"), "va en [0, 1]"),
+            (system("[body.rings]\ninner = 1.5\nouter = 2.0\nprofile = [[0.5, 1.0], [0.2, 0.5]]"), "en orden"),
+        ];
+        for (src, want) in cases {
+            let msg = invalid_msg(&src);
+            assert!(msg.contains(want), "'{}' no dice '{}'\n{}", msg, want, src);
+        }
+    }
+
+    #[test]
+    fn unknown_fields_are_parse_errors() {
+        assert!(matches!(parse_system(&system("colour = \"#FFFFFF\"")), Err(SystemError::Parse(_))));
+    }
+}
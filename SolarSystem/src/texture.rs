@@ -0,0 +1,153 @@
+use std::f32::consts::PI;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::image_io::{Image, read_image};
+use crate::math::Vec3;
+
+/// Un nivel de la cadena de mips, 0xRRGGBB como el FrameBuffer
+#[derive(Clone)]
+struct Level {
+    w: usize,
+    h: usize,
+    texels: Vec<u32>,
+}
+
+impl Level {
+    fn texel(&self, x: usize, y: usize) -> Vec3 {
+        let c = self.texels[y * self.w + x];
+        let ch = |sh: u32| ((c >> sh) & 0xFF) as f32 / 255.0;
+        Vec3::new(ch(16), ch(8), ch(0))
+    }
+
+    /// Mitad de tamaño promediando bloques de 2×2 (el borde impar se repite)
+    fn half(&self) -> Self {
+        let (w, h) = ((self.w / 2).max(1), (self.h / 2).max(1));
+        let mut texels = Vec::with_capacity(w * h);
+        for y in 0..h {
+            for x in 0..w {
+                let (x0, y0) = ((2 * x).min(self.w - 1), (2 * y).min(self.h - 1));
+                let (x1, y1) = ((x0 + 1).min(self.w - 1), (y0 + 1).min(self.h - 1));
+                let mut sum = [0u32; 3];
+                for &(xx, yy) in &[(x0, y0), (x1, y0), (x0, y1), (x1, y1)] {
+                    let c = self.texels[yy * self.w + xx];
+                    sum[0] += (c >> 16) & 0xFF;
+                    sum[1] += (c >> 8) & 0xFF;
+                    sum[2] += c & 0xFF;
+                }
+                let avg = |s: u32| (s + 2) / 4;
+                texels.push((avg(sum[0]) << 16) | (avg(sum[1]) << 8) | avg(sum[2]));
+            }
+        }
+        Self { w, h, texels }
+    }
+
+    /// Filtro bilineal; `u` da la vuelta (longitud) y `v` se recorta en los polos
+    fn bilinear(&self, u: f32, v: f32) -> Vec3 {
+        let x = u * self.w as f32 - 0.5;
+        let y = (v * self.h as f32 - 0.5).clamp(0.0, (self.h - 1) as f32);
+        let (fx, fy) = (x - x.floor(), y - y.floor());
+        let x0 = (x.floor() as i64).rem_euclid(self.w as i64) as usize;
+        let x1 = (x0 + 1) % self.w;
+        let y0 = y as usize;
+        let y1 = (y0 + 1).min(self.h - 1);
+        let top = Vec3::lerp(self.texel(x0, y0), self.texel(x1, y0), fx);
+        let bottom = Vec3::lerp(self.texel(x0, y1), self.texel(x1, y1), fx);
+        Vec3::lerp(top, bottom, fy)
+    }
+}
+
+/// Mapa equirectangular (longitud en x, latitud en y con el norte arriba)
+/// con su cadena de mips hasta 1×1
+#[derive(Clone)]
+pub struct Texture {
+    levels: Vec<Level>,
+}
+
+impl fmt::Debug for Texture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Texture({}x{}, {} mips)", self.width(), self.height(), self.levels.len())
+    }
+}
+
+impl Texture {
+    pub fn from_image(img: Image) -> Self {
+        let mut levels = vec![Level { w: img.w, h: img.h, texels: img.pixels }];
+        while let Some(last) = levels.last().filter(|l| l.w > 1 || l.h > 1) {
+            let next = last.half();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// Lee un PPM, PNG o TGA de disco
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(Self::from_image(read_image(path)?))
+    }
+
+    /// El mapa a tamaño completo (sin los mips)
+    pub fn to_image(&self) -> Image {
+        let l = &self.levels[0];
+        Image { w: l.w, h: l.h, pixels: l.texels.clone() }
+    }
+
+    pub fn width(&self) -> usize { self.levels[0].w }
+    pub fn height(&self) -> usize { self.levels[0].h }
+
+    /// Color promedio de todo el mapa (el último mip)
+    pub fn average(&self) -> Vec3 {
+        self.levels[self.levels.len() - 1].texel(0, 0)
+    }
+
+    /// Nivel de mip para una esfera de `radius_px` píxeles de radio en
+    /// pantalla: en el centro del disco un píxel cubre 1/radius_px radianes y
+    /// el mapa tiene `width / 2π` texels por radián
+    pub fn lod(&self, radius_px: f32) -> f32 {
+        let texels_per_px = self.width() as f32 / (2.0 * PI * radius_px.max(1.0));
+        texels_per_px.max(1.0).log2()
+    }
+
+    /// Muestra con filtro trilineal: bilineal en los dos mips vecinos a `lod`
+    pub fn sample(&self, u: f32, v: f32, lod: f32) -> Vec3 {
+        let top = self.levels.len() - 1;
+        let lod = lod.clamp(0.0, top as f32);
+        let l0 = lod.floor() as usize;
+        let a = self.levels[l0].bilinear(u, v);
+        let t = lod - l0 as f32;
+        if t <= 0.0 || l0 == top { return a; }
+        Vec3::lerp(a, self.levels[l0 + 1].bilinear(u, v), t)
+    }
+}
+
+/// Coordenadas (u, v) en 0..1 de un punto de la esfera unitaria en el marco
+/// del cuerpo (y = eje de giro): u crece hacia el este (a la derecha visto
+/// desde afuera con el norte arriba) y v = 0 es el polo norte
+pub fn equirect_uv(local: Vec3) -> (f32, f32) {
+    let lon = local.z.atan2(local.x);
+    let lat = local.y.clamp(-1.0, 1.0).asin();
+    (0.5 - lon / (2.0 * PI), 0.5 - lat / PI)
+}
+
+/// Inversa de `equirect_uv`: el punto de la esfera unitaria (marco del
+/// cuerpo) que cae en (u, v)
+pub fn equirect_dir(u: f32, v: f32) -> Vec3 {
+    let lon = (0.5 - u) * 2.0 * PI;
+    let lat = (0.5 - v) * PI;
+    let (sl, cl) = lat.sin_cos();
+    Vec3::new(cl * lon.cos(), sl, cl * lon.sin())
+}
+
+/// Mapas de un material pintado (`PlanetKind::Textured`); todos
+/// equirectangulares, cada uno del tamaño que quiera
+#[derive(Clone, Debug)]
+pub struct SurfaceMaps {
+    /// Color de la superficie
+    pub albedo: Texture,
+    /// Normales en espacio tangente (R = este, G = norte, B = hacia afuera)
+    pub normal: Option<Texture>,
+    /// Cuánto brilla cada punto (blanco = liso como el agua)
+    pub specular: Option<Texture>,
+    /// Luces del lado nocturno (ciudades, lava)
+    pub night: Option<Texture>,
+}
@@ -1,5 +1,20 @@
+use std::io;
+
 use minifb::{KeyRepeat, Scale, Window, WindowOptions};
 
+/// Lo que el loop principal necesita de una salida: teclado + presentar frames.
+/// La ventana de minifb y el modo headless lo implementan.
+pub trait Backend {
+    fn size(&self) -> (usize, usize);
+    fn is_open(&self) -> bool;
+    fn key_down(&self, k: Key) -> bool;
+    fn key_pressed(&self, k: Key) -> bool;
+    /// Muestra o guarda un frame; un error corta el loop
+    fn present(&mut self, buf: &[u32]) -> io::Result<()>;
+    /// Paso de simulación fijo (headless); None = tiempo real
+    fn fixed_dt(&self) -> Option<f32> { None }
+}
+
 pub struct WindowCtx {
     pub window: Window,
     pub width: usize,
@@ -15,15 +30,19 @@ impl WindowCtx {
         ).unwrap();
         Self { window, width, height }
     }
+}
+
+impl Backend for WindowCtx {
+    fn size(&self) -> (usize, usize) { (self.width, self.height) }
 
-    pub fn is_open(&self) -> bool { self.window.is_open() }
+    fn is_open(&self) -> bool { self.window.is_open() }
 
-    pub fn key_down(&self, k: Key) -> bool { self.window.is_key_down(k) }
+    fn key_down(&self, k: Key) -> bool { self.window.is_key_down(k) }
 
-    pub fn key_pressed(&self, k: Key) -> bool { self.window.is_key_pressed(k, KeyRepeat::No) }
+    fn key_pressed(&self, k: Key) -> bool { self.window.is_key_pressed(k, KeyRepeat::No) }
 
-    pub fn present(&mut self, buf: &[u32]) {
-        self.window.update_with_buffer(buf, self.width, self.height).unwrap();
+    fn present(&mut self, buf: &[u32]) -> io::Result<()> {
+        self.window.update_with_buffer(buf, self.width, self.height).map_err(io::Error::other)
     }
 }
 